@@ -0,0 +1,109 @@
+use anyhow::Result;
+use reqwest::Client;
+use rmcp::model::{CompleteRequestParam, CompletionInfo};
+
+use crate::crate_cache::CrateCache;
+use crate::rustdoc_parser;
+use crate::tools::search_crates::{crate_versions, suggest_similar_crates};
+
+/// Crates to ask crates.io for when completing a crate name, before keeping
+/// those that start with what was typed
+const CRATE_SEARCH_LIMIT: usize = 50;
+
+/// Complete an argument of a resource template or prompt. Arguments are
+/// recognized by name, both as spelled in URI templates (`crate`,
+/// `item_path`) and as in tool parameters (`crateName`, `itemPath`).
+pub async fn complete(
+    crates: &CrateCache,
+    client: &Client,
+    request: &CompleteRequestParam,
+) -> Result<CompletionInfo> {
+    let value = request.argument.value.trim();
+    // Arguments the client has already filled in
+    let context = |names: &[&str]| {
+        let arguments = request.context.as_ref()?;
+        names
+            .iter()
+            .find_map(|name| arguments.get_argument(name))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+
+    let values = match request.argument.name.as_str() {
+        "crate" | "crateName" => complete_crate_name(crates, client, value).await,
+        "version" | "fromVersion" | "toVersion" => match context(&["crate", "crateName"]) {
+            Some(crate_name) => complete_version(client, crate_name, value).await?,
+            None => Vec::new(),
+        },
+        "item_path" | "itemPath" => match context(&["crate", "crateName"]) {
+            Some(crate_name) => {
+                let version = context(&["version"]).filter(|version| *version != "latest");
+                // Completion runs as the user types, too often to wait on a
+                // download, so only crates fetched before are completed
+                let Some(krate) = crates.get_cached(crate_name, version, None).await? else {
+                    return Ok(limited(Vec::new()));
+                };
+                let completions =
+                    rustdoc_parser::complete_item_path(&krate, value, CompletionInfo::MAX_VALUES);
+                return Ok(CompletionInfo {
+                    has_more: Some(completions.total > completions.values.len()),
+                    total: Some(completions.total as u32),
+                    values: completions.values,
+                });
+            }
+            None => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+
+    Ok(limited(values))
+}
+
+/// Crate names starting with a prefix: crates fetched recently first, then
+/// the crates.io search results. Names match ignoring case and `-` vs `_`.
+async fn complete_crate_name(crates: &CrateCache, client: &Client, prefix: &str) -> Vec<String> {
+    let normalize = |name: &str| name.to_lowercase().replace('-', "_");
+    let normalized_prefix = normalize(prefix);
+
+    let mut names: Vec<String> = crates
+        .recent()
+        .into_iter()
+        .map(|cached| cached.crate_name)
+        .collect();
+    if !prefix.is_empty() {
+        // The crates already fetched are still worth offering without crates.io
+        match suggest_similar_crates(client, prefix, CRATE_SEARCH_LIMIT).await {
+            Ok(found) => names.extend(found),
+            Err(e) => tracing::warn!("Failed to search crates.io for '{}': {:#}", prefix, e),
+        }
+    }
+
+    let mut matches: Vec<String> = Vec::new();
+    for name in names {
+        if normalize(&name).starts_with(&normalized_prefix) && !matches.contains(&name) {
+            matches.push(name);
+        }
+    }
+    matches
+}
+
+/// Published versions of a crate starting with a prefix, newest first, with
+/// `latest` offered before them
+async fn complete_version(client: &Client, crate_name: &str, prefix: &str) -> Result<Vec<String>> {
+    let versions = crate_versions(client, crate_name).await?;
+    Ok(std::iter::once("latest".to_string())
+        .chain(versions)
+        .filter(|version| version.starts_with(prefix))
+        .collect())
+}
+
+/// Cut completions down to the most a response may hold
+fn limited(mut values: Vec<String>) -> CompletionInfo {
+    let total = values.len();
+    values.truncate(CompletionInfo::MAX_VALUES);
+    CompletionInfo {
+        has_more: Some(total > values.len()),
+        total: Some(total as u32),
+        values,
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::docs_fetcher::{CrateKey, DocsFetcher};
+use crate::rustdoc_parser::Crate;
+
+/// Configuration for the in-memory parsed-crate cache
+#[derive(Debug, Clone)]
+pub struct CrateCacheConfig {
+    /// Approximate memory budget, measured as the size of the source JSON
+    pub max_bytes: usize,
+    /// How long `latest` and semver-range lookups stay valid
+    pub ttl: Duration,
+}
+
+impl Default for CrateCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: 512 * 1024 * 1024,
+            ttl: Duration::from_secs(3600),
+        }
+    }
+}
+
+struct CacheEntry {
+    krate: Arc<Crate>,
+    /// Size of the JSON the crate was parsed from
+    size: usize,
+    loaded_at: Instant,
+    /// Value of the use counter when this entry was last accessed
+    last_used: u64,
+}
+
+/// A crate held in memory, as listed for clients
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedCrate {
+    pub crate_name: String,
+    /// The version the docs were built for, or the requested one if unknown
+    pub version: String,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<CrateKey, CacheEntry>,
+    total_size: usize,
+    use_counter: u64,
+}
+
+/// Size-bounded LRU cache of deserialized crates, so repeated lookups
+/// against the same crate skip downloading and deserializing the JSON
+pub struct CrateCache {
+    fetcher: Arc<DocsFetcher>,
+    config: CrateCacheConfig,
+    state: Mutex<CacheState>,
+}
+
+impl CrateCache {
+    pub fn new(fetcher: Arc<DocsFetcher>, config: CrateCacheConfig) -> Self {
+        Self {
+            fetcher,
+            config,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Get the parsed rustdoc JSON for a crate, fetching and parsing it on a miss
+    pub async fn get(
+        &self,
+        crate_name: &str,
+        version: Option<&str>,
+        target: Option<&str>,
+    ) -> Result<Arc<Crate>> {
+        let key = CrateKey::new(crate_name, version, target, None);
+
+        if let Some(krate) = self.lookup(&key) {
+            tracing::debug!("Using parsed crate from memory for {}", key);
+            return Ok(krate);
+        }
+
+        let fetched = self
+            .fetcher
+            .fetch_crate(crate_name, version, target, None)
+            .await?;
+
+        self.insert(key, fetched.krate.clone(), fetched.json_size);
+
+        Ok(fetched.krate)
+    }
+
+    /// Get the parsed rustdoc JSON for a crate only if it's held in memory or
+    /// in the disk cache, without downloading it
+    pub async fn get_cached(
+        &self,
+        crate_name: &str,
+        version: Option<&str>,
+        target: Option<&str>,
+    ) -> Result<Option<Arc<Crate>>> {
+        let key = CrateKey::new(crate_name, version, target, None);
+
+        if let Some(krate) = self.lookup(&key) {
+            return Ok(Some(krate));
+        }
+
+        let Some(loaded) = self
+            .fetcher
+            .load_cached(crate_name, version, target)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        self.insert(key, loaded.krate.clone(), loaded.json_size);
+
+        Ok(Some(loaded.krate))
+    }
+
+    /// The crates currently held in memory for the default target, most
+    /// recently used first
+    pub fn recent(&self) -> Vec<CachedCrate> {
+        let state = self.state.lock().unwrap();
+        let mut entries: Vec<_> = state
+            .entries
+            .iter()
+            .filter(|(key, _)| key.target.is_none())
+            .collect();
+        entries.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.last_used));
+
+        let mut crates: Vec<CachedCrate> = Vec::new();
+        for (key, entry) in entries {
+            // `latest` and the exact version it resolved to are the same docs
+            let version = entry
+                .krate
+                .crate_version
+                .clone()
+                .or_else(|| key.version.clone())
+                .unwrap_or_else(|| "latest".to_string());
+            let cached = CachedCrate {
+                crate_name: key.crate_name.clone(),
+                version,
+            };
+            if !crates.contains(&cached) {
+                crates.push(cached);
+            }
+        }
+        crates
+    }
+
+    fn lookup(&self, key: &CrateKey) -> Option<Arc<Crate>> {
+        let mut state = self.state.lock().unwrap();
+        state.use_counter += 1;
+        let use_counter = state.use_counter;
+
+        let expired = match state.entries.get_mut(key) {
+            Some(entry) => {
+                // Exact versions never change, `latest` and ranges can move on
+                if key.is_exact_version() || entry.loaded_at.elapsed() < self.config.ttl {
+                    entry.last_used = use_counter;
+                    return Some(entry.krate.clone());
+                }
+                true
+            }
+            None => false,
+        };
+
+        if expired {
+            if let Some(entry) = state.entries.remove(key) {
+                state.total_size -= entry.size;
+            }
+        }
+
+        None
+    }
+
+    fn insert(&self, key: CrateKey, krate: Arc<Crate>, size: usize) {
+        if size > self.config.max_bytes {
+            tracing::debug!(
+                "Not caching {} in memory: {} bytes exceeds limit",
+                key,
+                size
+            );
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.use_counter += 1;
+        let entry = CacheEntry {
+            krate,
+            size,
+            loaded_at: Instant::now(),
+            last_used: state.use_counter,
+        };
+
+        state.total_size += size;
+        if let Some(previous) = state.entries.insert(key, entry) {
+            state.total_size -= previous.size;
+        }
+
+        // Evict least recently used crates until we fit the budget again
+        while state.total_size > self.config.max_bytes {
+            let Some(oldest) = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            if let Some(entry) = state.entries.remove(&oldest) {
+                tracing::debug!("Evicting parsed crate {} from memory", oldest);
+                state.total_size -= entry.size;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::Client;
+
+    use super::*;
+    use crate::disk_cache::{DiskCache, DiskCacheConfig};
+
+    /// Rustdoc JSON for a crate with nothing in it
+    const EMPTY_CRATE: &str = r#"{
+        "root": 0,
+        "crate_version": "1.0.0",
+        "includes_private": false,
+        "index": {},
+        "paths": {},
+        "external_crates": {},
+        "target": {"triple": "x86_64-unknown-linux-gnu", "target_features": []},
+        "format_version": 57
+    }"#;
+
+    #[tokio::test]
+    async fn get_cached_reads_the_disk_cache_without_downloading() {
+        let dir = tempfile::tempdir().unwrap();
+        let disk = DiskCache::new(DiskCacheConfig {
+            dir: dir.path().to_path_buf(),
+            max_bytes: 1024 * 1024,
+            ttl: Duration::from_secs(3600),
+        });
+        let key = CrateKey::new("empty", Some("1.0.0"), None, None);
+        disk.put(&key, "1.0.0", 57, EMPTY_CRATE.to_string())
+            .await
+            .unwrap();
+
+        let fetcher = Arc::new(DocsFetcher::new(Client::new(), Some(disk)));
+        let crates = CrateCache::new(fetcher, CrateCacheConfig::default());
+
+        let krate = crates
+            .get_cached("empty", Some("1.0.0"), None)
+            .await
+            .unwrap();
+        assert_eq!(krate.unwrap().crate_version.as_deref(), Some("1.0.0"));
+        assert_eq!(crates.recent().len(), 1);
+
+        // Downloading a name docs.rs can't have would fail rather than give `None`
+        let missing = crates.get_cached("not a crate", Some("1.0.0"), None).await;
+        assert!(missing.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn get_cached_without_a_disk_cache_only_sees_memory() {
+        let fetcher = Arc::new(DocsFetcher::new(Client::new(), None));
+        let crates = CrateCache::new(fetcher, CrateCacheConfig::default());
+        let krate = crates.get_cached("serde", None, None).await.unwrap();
+        assert!(krate.is_none());
+    }
+}
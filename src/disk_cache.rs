@@ -0,0 +1,465 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::docs_fetcher::CrateKey;
+
+/// zstd compression level used for cached bodies
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Configuration for the on-disk rustdoc JSON cache
+#[derive(Debug, Clone)]
+pub struct DiskCacheConfig {
+    /// Directory holding the cache
+    pub dir: PathBuf,
+    /// Maximum total size of cached bodies in bytes
+    pub max_bytes: u64,
+    /// How long `latest` and semver-range lookups stay valid
+    pub ttl: Duration,
+}
+
+impl DiskCacheConfig {
+    /// Default cache directory (`$XDG_CACHE_HOME/docsrs-mcp` on Linux)
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("docsrs-mcp"))
+    }
+}
+
+/// Alias from a requested crate/version/target/format to a cached entry
+#[derive(Debug, Serialize, Deserialize)]
+struct Alias {
+    /// Hash of the entry holding the body
+    entry: String,
+    /// Unix timestamp (seconds) of when the body was fetched
+    fetched_at: u64,
+}
+
+/// Content-addressed on-disk cache for rustdoc JSON bodies.
+///
+/// Bodies are stored zstd-compressed under `entries/`, keyed by crate, resolved
+/// version, target and format version. Requests are mapped to entries through
+/// small alias files under `aliases/`, so `latest` or `~1` can expire after the
+/// configured TTL while the immutable entry for the resolved version is kept.
+/// When the cache grows past its size limit the least recently used entries are
+/// evicted.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    config: DiskCacheConfig,
+}
+
+impl DiskCache {
+    pub fn new(config: DiskCacheConfig) -> Self {
+        Self { config }
+    }
+
+    /// Look up the rustdoc JSON for a request, if a valid entry is cached
+    pub async fn get(&self, key: &CrateKey) -> Result<Option<String>> {
+        let cache = self.clone();
+        let key = key.clone();
+        tokio::task::spawn_blocking(move || cache.get_blocking(&key))
+            .await
+            .context("Disk cache lookup task failed")?
+    }
+
+    /// Store the rustdoc JSON fetched for a request
+    pub async fn put(
+        &self,
+        key: &CrateKey,
+        resolved_version: &str,
+        format_version: u32,
+        body: String,
+    ) -> Result<()> {
+        let cache = self.clone();
+        let key = key.clone();
+        let resolved_version = resolved_version.to_string();
+        tokio::task::spawn_blocking(move || {
+            cache.put_blocking(&key, &resolved_version, format_version, &body)
+        })
+        .await
+        .context("Disk cache store task failed")?
+    }
+
+    fn entries_dir(&self) -> PathBuf {
+        self.config.dir.join("entries")
+    }
+
+    fn aliases_dir(&self) -> PathBuf {
+        self.config.dir.join("aliases")
+    }
+
+    fn entry_path(&self, hash: &str) -> PathBuf {
+        self.entries_dir().join(format!("{}.json.zst", hash))
+    }
+
+    fn alias_path(&self, hash: &str) -> PathBuf {
+        self.aliases_dir().join(format!("{}.json", hash))
+    }
+
+    fn get_blocking(&self, key: &CrateKey) -> Result<Option<String>> {
+        let alias_path = self.alias_path(&hash_key(&request_key_parts(key)));
+
+        let alias: Alias = match fs::read(&alias_path) {
+            Ok(bytes) => match serde_json::from_slice(&bytes) {
+                Ok(alias) => alias,
+                Err(e) => {
+                    tracing::warn!("Discarding corrupt cache alias {:?}: {}", alias_path, e);
+                    let _ = fs::remove_file(&alias_path);
+                    return Ok(None);
+                }
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).context("Failed to read cache alias"),
+        };
+
+        // Exact versions are immutable on docs.rs, everything else expires
+        if !key.is_exact_version() {
+            let age = unix_now().saturating_sub(alias.fetched_at);
+            if age > self.config.ttl.as_secs() {
+                tracing::debug!("Cache alias for {} expired ({}s old)", key, age);
+                return Ok(None);
+            }
+        }
+
+        let entry_path = self.entry_path(&alias.entry);
+        let compressed = match fs::read(&entry_path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                // The entry was evicted, so the alias is dangling
+                let _ = fs::remove_file(&alias_path);
+                return Ok(None);
+            }
+            Err(e) => return Err(e).context("Failed to read cache entry"),
+        };
+
+        let body = match zstd::decode_all(&compressed[..])
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| String::from_utf8(bytes).map_err(anyhow::Error::from))
+        {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::warn!("Discarding corrupt cache entry {:?}: {}", entry_path, e);
+                let _ = fs::remove_file(&entry_path);
+                let _ = fs::remove_file(&alias_path);
+                return Ok(None);
+            }
+        };
+
+        // Mark the entry as recently used for LRU eviction
+        if let Err(e) = touch(&entry_path) {
+            tracing::debug!("Failed to update cache entry mtime {:?}: {}", entry_path, e);
+        }
+
+        Ok(Some(body))
+    }
+
+    fn put_blocking(
+        &self,
+        key: &CrateKey,
+        resolved_version: &str,
+        format_version: u32,
+        body: &str,
+    ) -> Result<()> {
+        fs::create_dir_all(self.entries_dir()).context("Failed to create cache directory")?;
+        fs::create_dir_all(self.aliases_dir()).context("Failed to create cache directory")?;
+
+        let entry_hash = hash_key(&[
+            key.crate_name.as_str(),
+            resolved_version,
+            key.target.as_deref().unwrap_or(""),
+            &format_version.to_string(),
+        ]);
+        let entry_path = self.entry_path(&entry_hash);
+
+        if entry_path.exists() {
+            touch(&entry_path).context("Failed to update cache entry")?;
+        } else {
+            let compressed = zstd::encode_all(body.as_bytes(), COMPRESSION_LEVEL)
+                .context("Failed to compress rustdoc JSON")?;
+            write_atomic(&entry_path, &compressed).context("Failed to write cache entry")?;
+            tracing::debug!(
+                "Cached {} ({} bytes compressed) as {}",
+                key,
+                compressed.len(),
+                entry_hash
+            );
+        }
+
+        let alias = Alias {
+            entry: entry_hash,
+            fetched_at: unix_now(),
+        };
+        let alias_json = serde_json::to_vec(&alias)?;
+
+        // Besides the request itself, record the resolved version (with both the
+        // requested and the actual format version) so later exact lookups hit too
+        let resolved_keys = [key.format_version, Some(format_version)].map(|fmt| CrateKey {
+            version: Some(resolved_version.to_string()),
+            format_version: fmt,
+            ..key.clone()
+        });
+
+        for alias_key in std::iter::once(key).chain(&resolved_keys) {
+            write_atomic(
+                &self.alias_path(&hash_key(&request_key_parts(alias_key))),
+                &alias_json,
+            )
+            .context("Failed to write cache alias")?;
+        }
+
+        self.evict()
+    }
+
+    /// Remove least recently used entries until the cache fits its size limit
+    fn evict(&self) -> Result<()> {
+        let mut entries = Vec::new();
+        let mut total: u64 = 0;
+
+        for dir_entry in fs::read_dir(self.entries_dir()).context("Failed to list cache")? {
+            let dir_entry = dir_entry?;
+            let metadata = dir_entry.metadata()?;
+            if !metadata.is_file() {
+                continue;
+            }
+            let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+            total += metadata.len();
+            entries.push((modified, metadata.len(), dir_entry.path()));
+        }
+
+        if total <= self.config.max_bytes {
+            return Ok(());
+        }
+
+        entries.sort_by_key(|(modified, _, _)| *modified);
+
+        for (_, size, path) in entries {
+            if total <= self.config.max_bytes {
+                break;
+            }
+            tracing::debug!("Evicting cache entry {:?} ({} bytes)", path, size);
+            match fs::remove_file(&path) {
+                Ok(()) => total -= size,
+                Err(e) => tracing::warn!("Failed to evict cache entry {:?}: {}", path, e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Components identifying a request, as given by the caller
+fn request_key_parts(key: &CrateKey) -> [String; 4] {
+    [
+        key.crate_name.clone(),
+        key.version.clone().unwrap_or_else(|| "latest".to_string()),
+        key.target.clone().unwrap_or_default(),
+        key.format_version
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "latest".to_string()),
+    ]
+}
+
+/// Hash key components into a filesystem-safe hex digest
+fn hash_key<S: AsRef<str>>(parts: &[S]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_ref().as_bytes());
+        hasher.update([0]);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Write a file by renaming a temporary file into place, so readers never see partial data
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp_path = path.with_extension(format!("tmp.{}", std::process::id()));
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path)
+}
+
+fn touch(path: &Path) -> io::Result<()> {
+    fs::File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(SystemTime::now())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3600;
+
+    fn cache(dir: &Path, max_bytes: u64) -> DiskCache {
+        DiskCache::new(DiskCacheConfig {
+            dir: dir.to_path_buf(),
+            max_bytes,
+            ttl: Duration::from_secs(HOUR),
+        })
+    }
+
+    fn key(crate_name: &str, version: Option<&str>) -> CrateKey {
+        CrateKey::new(crate_name, version, None, None)
+    }
+
+    fn body(crate_name: &str) -> String {
+        format!(r#"{{"crate":"{}","format_version":57}}"#, crate_name).repeat(100)
+    }
+
+    fn read_alias(cache: &DiskCache, key: &CrateKey) -> Alias {
+        let path = cache.alias_path(&hash_key(&request_key_parts(key)));
+        serde_json::from_slice(&fs::read(path).unwrap()).unwrap()
+    }
+
+    fn entry_path(cache: &DiskCache, key: &CrateKey) -> PathBuf {
+        cache.entry_path(&read_alias(cache, key).entry)
+    }
+
+    /// Pretend the alias for a request was written `secs` seconds ago
+    fn age_alias(cache: &DiskCache, key: &CrateKey, secs: u64) {
+        let mut alias = read_alias(cache, key);
+        alias.fetched_at -= secs;
+        let path = cache.alias_path(&hash_key(&request_key_parts(key)));
+        fs::write(path, serde_json::to_vec(&alias).unwrap()).unwrap();
+    }
+
+    fn set_last_used(path: &Path, secs_ago: u64) {
+        let time = SystemTime::now() - Duration::from_secs(secs_ago);
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn latest_expires_after_ttl_but_exact_version_does_not() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache(dir.path(), u64::MAX);
+        let latest = key("serde", None);
+        let exact = key("serde", Some("1.0.200"));
+
+        cache
+            .put_blocking(&latest, "1.0.200", 57, &body("serde"))
+            .unwrap();
+        assert_eq!(cache.get_blocking(&latest).unwrap(), Some(body("serde")));
+        assert_eq!(cache.get_blocking(&exact).unwrap(), Some(body("serde")));
+
+        age_alias(&cache, &latest, 2 * HOUR);
+        age_alias(&cache, &exact, 2 * HOUR);
+        assert_eq!(cache.get_blocking(&latest).unwrap(), None);
+        assert_eq!(cache.get_blocking(&exact).unwrap(), Some(body("serde")));
+    }
+
+    #[test]
+    fn semver_range_expires_after_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache(dir.path(), u64::MAX);
+        let range = key("serde", Some("~1"));
+
+        cache
+            .put_blocking(&range, "1.0.200", 57, &body("serde"))
+            .unwrap();
+        age_alias(&cache, &range, HOUR / 2);
+        assert_eq!(cache.get_blocking(&range).unwrap(), Some(body("serde")));
+        age_alias(&cache, &range, HOUR);
+        assert_eq!(cache.get_blocking(&range).unwrap(), None);
+    }
+
+    #[test]
+    fn evicts_least_recently_used_entries_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let unbounded = cache(dir.path(), u64::MAX);
+        let keys = ["anyhow", "serde", "tokio"].map(|name| key(name, Some("1.0.0")));
+        for key in &keys {
+            unbounded
+                .put_blocking(key, "1.0.0", 57, &body(&key.crate_name))
+                .unwrap();
+        }
+        let [anyhow, serde, tokio] = &keys;
+
+        // anyhow was stored first, but reading it makes it the most recent
+        set_last_used(&entry_path(&unbounded, anyhow), 300);
+        set_last_used(&entry_path(&unbounded, serde), 200);
+        set_last_used(&entry_path(&unbounded, tokio), 100);
+        assert!(unbounded.get_blocking(anyhow).unwrap().is_some());
+
+        let size = |key: &CrateKey| fs::metadata(entry_path(&unbounded, key)).unwrap().len();
+        let bounded = cache(dir.path(), size(anyhow) + size(tokio));
+        bounded.evict().unwrap();
+
+        assert_eq!(bounded.get_blocking(serde).unwrap(), None);
+        assert!(bounded.get_blocking(anyhow).unwrap().is_some());
+        assert!(bounded.get_blocking(tokio).unwrap().is_some());
+
+        // Storing another crate evicts the least recently used one left
+        set_last_used(&entry_path(&bounded, anyhow), 100);
+        set_last_used(&entry_path(&bounded, tokio), 200);
+        let rayon = key("rayon", Some("1.0.0"));
+        bounded
+            .put_blocking(&rayon, "1.0.0", 57, &body("rayon"))
+            .unwrap();
+        assert_eq!(bounded.get_blocking(tokio).unwrap(), None);
+        assert!(bounded.get_blocking(anyhow).unwrap().is_some());
+        assert!(bounded.get_blocking(&rayon).unwrap().is_some());
+    }
+
+    #[test]
+    fn recovers_from_truncated_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache(dir.path(), u64::MAX);
+        let serde = key("serde", Some("1.0.0"));
+        cache
+            .put_blocking(&serde, "1.0.0", 57, &body("serde"))
+            .unwrap();
+
+        let entry = entry_path(&cache, &serde);
+        let compressed = fs::read(&entry).unwrap();
+        fs::write(&entry, &compressed[..compressed.len() / 2]).unwrap();
+
+        assert_eq!(cache.get_blocking(&serde).unwrap(), None);
+        assert!(!entry.exists());
+
+        // The next fetch stores the body again
+        cache
+            .put_blocking(&serde, "1.0.0", 57, &body("serde"))
+            .unwrap();
+        assert_eq!(cache.get_blocking(&serde).unwrap(), Some(body("serde")));
+    }
+
+    #[test]
+    fn recovers_from_corrupt_entry_and_alias() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache(dir.path(), u64::MAX);
+        let serde = key("serde", Some("1.0.0"));
+        let anyhow = key("anyhow", Some("1.0.0"));
+        for key in [&serde, &anyhow] {
+            cache
+                .put_blocking(key, "1.0.0", 57, &body(&key.crate_name))
+                .unwrap();
+        }
+
+        fs::write(entry_path(&cache, &serde), b"not zstd").unwrap();
+        let alias = cache.alias_path(&hash_key(&request_key_parts(&anyhow)));
+        fs::write(&alias, b"{").unwrap();
+
+        assert_eq!(cache.get_blocking(&serde).unwrap(), None);
+        assert_eq!(cache.get_blocking(&anyhow).unwrap(), None);
+        assert!(!alias.exists());
+    }
+}
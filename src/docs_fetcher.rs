@@ -1,15 +1,343 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
 use anyhow::{anyhow, Context, Result};
 use async_compression::tokio::bufread::ZstdDecoder;
+use futures::future::{BoxFuture, FutureExt, Shared};
 use reqwest::Client;
 use tokio::io::AsyncReadExt;
+use tokio::sync::watch;
+
+use crate::disk_cache::DiskCache;
+use crate::error::DocsError;
+use crate::progress::ProgressReporter;
+use crate::rustdoc_parser::{self, Crate};
+
+/// Bytes downloaded between progress updates
+const PROGRESS_STEP: u64 = 1024 * 1024;
+
+/// Identifies a rustdoc JSON request: crate, version, target and format version
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CrateKey {
+    pub crate_name: String,
+    pub version: Option<String>,
+    pub target: Option<String>,
+    pub format_version: Option<u32>,
+}
+
+impl CrateKey {
+    pub fn new(
+        crate_name: &str,
+        version: Option<&str>,
+        target: Option<&str>,
+        format_version: Option<u32>,
+    ) -> Self {
+        Self {
+            crate_name: crate_name.to_string(),
+            version: version.map(str::to_string),
+            target: target.map(str::to_string),
+            format_version,
+        }
+    }
+
+    /// Whether the requested version is an exact release (and therefore immutable on docs.rs)
+    pub fn is_exact_version(&self) -> bool {
+        self.version.as_deref().is_some_and(is_exact_version)
+    }
+}
+
+impl fmt::Display for CrateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}@{}",
+            self.crate_name,
+            self.version.as_deref().unwrap_or("latest")
+        )?;
+        if let Some(target) = &self.target {
+            write!(f, " ({})", target)?;
+        }
+        if let Some(format_version) = self.format_version {
+            write!(f, " [format {}]", format_version)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse rustdoc JSON into a crate. Deserializing large crates takes a
+/// while, so it runs off the async workers.
+async fn parse(json_str: String, cancelled: Arc<AtomicBool>) -> Result<FetchedCrate> {
+    let json_size = json_str.len();
+    let krate =
+        tokio::task::spawn_blocking(move || rustdoc_parser::parse_crate(&json_str, &cancelled))
+            .await
+            .context("Rustdoc JSON parsing task failed")??;
+
+    Ok(FetchedCrate {
+        krate: Arc::new(krate),
+        json_size,
+    })
+}
+
+/// Check whether a version string names an exact release like "1.2.3" or "1.0.0-beta.1"
+/// rather than "latest" or a semver range like "~4" or "^1.2"
+pub fn is_exact_version(version: &str) -> bool {
+    let core = version.split(['-', '+']).next().unwrap_or("");
+    let parts: Vec<&str> = core.split('.').collect();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// A fetched and parsed crate
+#[derive(Clone)]
+pub struct FetchedCrate {
+    pub krate: Arc<Crate>,
+    /// Size of the rustdoc JSON the crate was parsed from
+    pub json_size: usize,
+}
+
+/// How much of a rustdoc JSON download has arrived
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    /// Size of the body, when the server sent one
+    pub total: Option<u64>,
+}
+
+/// A fetch shared between all concurrent requests for the same crate
+type SharedFetch = Shared<BoxFuture<'static, Result<FetchedCrate, Arc<anyhow::Error>>>>;
+
+struct InFlight {
+    /// Tells this fetch apart from later ones for the same crate
+    id: u64,
+    fetch: SharedFetch,
+    progress: watch::Receiver<DownloadProgress>,
+    /// Set once every request gave up on the fetch, to stop a parse in progress
+    cancelled: Arc<AtomicBool>,
+    /// Requests waiting on the fetch; once all are cancelled it is dropped
+    waiters: usize,
+}
 
 pub struct DocsFetcher {
     client: Client,
+    cache: Option<DiskCache>,
+    in_flight: Mutex<HashMap<CrateKey, InFlight>>,
+    next_fetch_id: AtomicU64,
+}
+
+/// A request waiting on an in-flight fetch, which gives up its share of the
+/// fetch when dropped
+struct Waiter<'a> {
+    fetcher: &'a DocsFetcher,
+    key: &'a CrateKey,
+    id: u64,
+}
+
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self.fetcher.in_flight.lock().unwrap();
+        let Some(entry) = in_flight
+            .get_mut(self.key)
+            .filter(|entry| entry.id == self.id)
+        else {
+            // The fetch finished and removed itself
+            return;
+        };
+        entry.waiters -= 1;
+        if entry.waiters == 0 {
+            tracing::info!(
+                "Abandoning fetch for {}, its requests were cancelled",
+                self.key
+            );
+            // Dropping the last handle to the fetch aborts the download; a
+            // parse already running can't be dropped, so it is told to stop
+            // at its next check
+            let abandoned = in_flight.remove(self.key);
+            drop(in_flight);
+            if let Some(abandoned) = abandoned {
+                abandoned.cancelled.store(true, Ordering::Relaxed);
+            }
+        }
+    }
 }
 
 impl DocsFetcher {
-    pub fn new(client: Client) -> Self {
-        Self { client }
+    pub fn new(client: Client, cache: Option<DiskCache>) -> Self {
+        Self {
+            client,
+            cache,
+            in_flight: Mutex::new(HashMap::new()),
+            next_fetch_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Fetch and parse rustdoc JSON for a crate.
+    ///
+    /// Concurrent calls for the same crate, version, target and format version
+    /// share a single download and parse. Each reports the download's progress
+    /// to its own client. Once every caller has given up on it the download is
+    /// aborted, and a parse in progress stops between its deserialization and
+    /// conversion phases.
+    pub async fn fetch_crate(
+        self: &Arc<Self>,
+        crate_name: &str,
+        version: Option<&str>,
+        target: Option<&str>,
+        format_version: Option<u32>,
+    ) -> Result<FetchedCrate> {
+        let key = CrateKey::new(crate_name, version, target, format_version);
+
+        let (id, fetch, progress) = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get_mut(&key) {
+                Some(entry) => {
+                    tracing::debug!("Joining in-flight fetch for {}", key);
+                    entry.waiters += 1;
+                    (entry.id, entry.fetch.clone(), entry.progress.clone())
+                }
+                None => {
+                    let id = self.next_fetch_id.fetch_add(1, Ordering::Relaxed);
+                    let (progress_tx, progress) = watch::channel(DownloadProgress::default());
+                    let cancelled = Arc::new(AtomicBool::new(false));
+                    let this = Arc::clone(self);
+                    let fetch_key = key.clone();
+                    let fetch_cancelled = Arc::clone(&cancelled);
+                    let fetch = async move {
+                        let result = this
+                            .fetch_and_parse(&fetch_key, &progress_tx, fetch_cancelled)
+                            .await
+                            .map_err(Arc::new);
+                        let mut in_flight = this.in_flight.lock().unwrap();
+                        if in_flight
+                            .get(&fetch_key)
+                            .is_some_and(|entry| entry.id == id)
+                        {
+                            in_flight.remove(&fetch_key);
+                        }
+                        result
+                    }
+                    .boxed()
+                    .shared();
+                    in_flight.insert(
+                        key.clone(),
+                        InFlight {
+                            id,
+                            fetch: fetch.clone(),
+                            progress: progress.clone(),
+                            cancelled,
+                            waiters: 1,
+                        },
+                    );
+                    (id, fetch, progress)
+                }
+            }
+        };
+        let _waiter = Waiter {
+            fetcher: self,
+            key: &key,
+            id,
+        };
+
+        let result = match ProgressReporter::current() {
+            Some(reporter) => wait_with_progress(&key, fetch, progress, reporter).await,
+            None => fetch.await,
+        };
+
+        // Keep typed errors typed, so clients still get their error codes
+        result.map_err(|e| match DocsError::find(&e) {
+            Some(error) => error.clone().into(),
+            None => anyhow!("{:#}", e),
+        })
+    }
+
+    async fn fetch_and_parse(
+        &self,
+        key: &CrateKey,
+        progress: &watch::Sender<DownloadProgress>,
+        cancelled: Arc<AtomicBool>,
+    ) -> Result<FetchedCrate> {
+        let mut json_str = self
+            .fetch_crate_json(
+                &key.crate_name,
+                key.version.as_deref(),
+                key.target.as_deref(),
+                key.format_version,
+                progress,
+            )
+            .await?;
+
+        // docs.rs serves the newest format by default, which may be newer than we
+        // support; it keeps older formats available under an explicit version
+        let format_version = rustdoc_parser::get_format_version(&json_str)?;
+        if key.format_version.is_none()
+            && !rustdoc_parser::is_supported_format_version(format_version)
+        {
+            tracing::info!(
+                "{} uses unsupported format version {}, retrying with format {}",
+                key,
+                format_version,
+                rustdoc_parser::LATEST_FORMAT_VERSION
+            );
+            match self
+                .fetch_crate_json(
+                    &key.crate_name,
+                    key.version.as_deref(),
+                    key.target.as_deref(),
+                    Some(rustdoc_parser::LATEST_FORMAT_VERSION),
+                    progress,
+                )
+                .await
+            {
+                Ok(body) => json_str = body,
+                Err(e) => tracing::warn!(
+                    "Format {} unavailable for {}, parsing format {} untyped: {:#}",
+                    rustdoc_parser::LATEST_FORMAT_VERSION,
+                    key,
+                    format_version,
+                    e
+                ),
+            }
+        }
+        parse(json_str, cancelled).await
+    }
+
+    /// Parse rustdoc JSON for a crate from the on-disk cache alone, without
+    /// downloading anything. Returns `None` if the crate isn't cached.
+    pub async fn load_cached(
+        &self,
+        crate_name: &str,
+        version: Option<&str>,
+        target: Option<&str>,
+    ) -> Result<Option<FetchedCrate>> {
+        let Some(cache) = &self.cache else {
+            return Ok(None);
+        };
+        let key = CrateKey::new(crate_name, version, target, None);
+        let Some(mut json_str) = cache.get(&key).await? else {
+            return Ok(None);
+        };
+
+        // Prefer the supported format a fetch would have retried with
+        let format_version = rustdoc_parser::get_format_version(&json_str)?;
+        if !rustdoc_parser::is_supported_format_version(format_version) {
+            let key = CrateKey::new(
+                crate_name,
+                version,
+                target,
+                Some(rustdoc_parser::LATEST_FORMAT_VERSION),
+            );
+            if let Some(body) = cache.get(&key).await? {
+                json_str = body;
+            }
+        }
+
+        parse(json_str, Arc::new(AtomicBool::new(false)))
+            .await
+            .map(Some)
     }
 
     /// Build the docs.rs JSON URL for a crate
@@ -44,45 +372,89 @@ impl DocsFetcher {
         url
     }
 
-    /// Fetch rustdoc JSON for a crate
+    /// Fetch rustdoc JSON for a crate, using the on-disk cache when possible
     pub async fn fetch_crate_json(
         &self,
         crate_name: &str,
         version: Option<&str>,
         target: Option<&str>,
         format_version: Option<u32>,
+        progress: &watch::Sender<DownloadProgress>,
+    ) -> Result<String> {
+        let key = CrateKey::new(crate_name, version, target, format_version);
+
+        if let Some(cache) = &self.cache {
+            match cache.get(&key).await {
+                Ok(Some(body)) => {
+                    tracing::info!("Using cached rustdoc JSON for {}", key);
+                    return Ok(body);
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to read disk cache for {}: {:#}", key, e),
+            }
+        }
+
+        let body = self
+            .download_crate_json(crate_name, version, target, format_version, progress)
+            .await?;
+
+        if let Some(cache) = &self.cache {
+            let info = rustdoc_parser::get_version_info(&body)?;
+            // Fall back to the requested version if rustdoc didn't record one
+            let resolved_version = info
+                .crate_version
+                .or_else(|| version.map(str::to_string))
+                .unwrap_or_else(|| "latest".to_string());
+
+            if let Err(e) = cache
+                .put(&key, &resolved_version, info.format_version, body.clone())
+                .await
+            {
+                tracing::warn!("Failed to store {} in disk cache: {:#}", key, e);
+            }
+        }
+
+        Ok(body)
+    }
+
+    /// Download rustdoc JSON for a crate from docs.rs, publishing how much of
+    /// the body has arrived as it streams in
+    async fn download_crate_json(
+        &self,
+        crate_name: &str,
+        version: Option<&str>,
+        target: Option<&str>,
+        format_version: Option<u32>,
+        progress: &watch::Sender<DownloadProgress>,
     ) -> Result<String> {
         let url = self.build_json_url(crate_name, version, target, format_version);
 
         tracing::info!("Fetching rustdoc JSON from: {}", url);
 
-        let response = self
+        let mut response = self
             .client
             .get(&url)
             .send()
             .await
-            .context("Failed to send request to docs.rs")?;
+            .map_err(|e| DocsError::network("docs.rs", &e))?;
 
         // Log response headers for debugging
         tracing::debug!("Response headers for {}: {:?}", url, response.headers());
 
         if response.status() == 404 {
-            let version_str = version
-                .map(|v| format!(" version {}", v))
-                .unwrap_or_default();
-            return Err(anyhow!(
-                "Crate '{}'{} not found. Note: docs.rs started building rustdoc JSON on 2023-05-23, so older releases may not have JSON available yet.",
-                crate_name,
-                version_str
-            ));
+            let crate_name = crate_name.to_string();
+            return Err(match version.filter(|v| *v != "latest") {
+                Some(version) => DocsError::VersionNotFound {
+                    crate_name,
+                    version: version.to_string(),
+                },
+                None => DocsError::CrateNotFound { crate_name },
+            }
+            .into());
         }
 
         if !response.status().is_success() {
-            return Err(anyhow!(
-                "Failed to fetch rustdoc JSON: HTTP {} {}",
-                response.status().as_u16(),
-                response.status().canonical_reason().unwrap_or("Unknown")
-            ));
+            return Err(DocsError::upstream_status("docs.rs", response.status()).into());
         }
         tracing::debug!("Response is successful, attempting to read body");
 
@@ -94,11 +466,32 @@ impl DocsFetcher {
             .map(|s| s.eq_ignore_ascii_case("zstd"))
             .unwrap_or(false);
 
-        // Get the response body as bytes
-        let bytes = response
-            .bytes()
+        // Stream the response body, so progress can be reported and the
+        // download stops as soon as this future is dropped
+        let total = response.content_length();
+        let mut bytes = Vec::with_capacity(total.unwrap_or(0) as usize);
+        let mut reported = 0;
+        progress.send_replace(DownloadProgress {
+            downloaded: 0,
+            total,
+        });
+        while let Some(chunk) = response
+            .chunk()
             .await
-            .context("Failed to read response body")?;
+            .map_err(|e| DocsError::network("docs.rs", &e))
+            .context("Failed to read response body")?
+        {
+            bytes.extend_from_slice(&chunk);
+            let downloaded = bytes.len() as u64;
+            if downloaded - reported >= PROGRESS_STEP {
+                reported = downloaded;
+                progress.send_replace(DownloadProgress { downloaded, total });
+            }
+        }
+        progress.send_replace(DownloadProgress {
+            downloaded: bytes.len() as u64,
+            total,
+        });
 
         tracing::debug!("Body length: {} bytes", bytes.len());
 
@@ -117,7 +510,7 @@ impl DocsFetcher {
                 .context("Failed to decompress zstd content")?;
             decompressed
         } else {
-            String::from_utf8(bytes.to_vec()).context("Response body is not valid UTF-8")?
+            String::from_utf8(bytes).context("Response body is not valid UTF-8")?
         };
 
         tracing::debug!("Decoded body length: {} chars", body.len());
@@ -128,11 +521,10 @@ impl DocsFetcher {
 
         // Check if we got HTML instead of JSON (docs.rs returns HTML when JSON is not available)
         if body.trim().starts_with("<!DOCTYPE") || body.trim().starts_with("<html") {
-            return Err(anyhow!(
-                "Crate '{}' does not have rustdoc JSON available. Docs.rs returned HTML instead. \
-         Note: docs.rs only builds rustdoc JSON for crates published after 2023-05-23.",
-                crate_name
-            ));
+            return Err(DocsError::NoJsonAvailable {
+                crate_name: crate_name.to_string(),
+            }
+            .into());
         }
 
         // Log the first part of the response for debugging
@@ -158,3 +550,26 @@ impl DocsFetcher {
         Ok(body)
     }
 }
+
+/// Wait for a shared fetch, passing its download progress on to a client
+async fn wait_with_progress(
+    key: &CrateKey,
+    mut fetch: SharedFetch,
+    mut progress: watch::Receiver<DownloadProgress>,
+    reporter: ProgressReporter,
+) -> Result<FetchedCrate, Arc<anyhow::Error>> {
+    let message = format!("Downloading rustdoc JSON for {}", key);
+    loop {
+        tokio::select! {
+            result = &mut fetch => return result,
+            changed = progress.changed() => {
+                if changed.is_err() {
+                    // The fetch is done with downloading
+                    return fetch.await;
+                }
+                let DownloadProgress { downloaded, total } = *progress.borrow_and_update();
+                reporter.report(downloaded, total, message.clone()).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use thiserror::Error;
+
+use crate::rustdoc_parser::LATEST_FORMAT_VERSION;
+
+/// Why a crate's docs may be missing even though the crate exists
+const JSON_AVAILABILITY_NOTE: &str = "Note: docs.rs started building rustdoc JSON on \
+     2023-05-23, so older releases may not have JSON available yet.";
+
+/// Failures reported back to clients, each with a stable machine-readable code.
+///
+/// Functions keep returning `anyhow::Result`; these are raised where the
+/// failure is detected and found again in the error chain with [`DocsError::find`].
+#[derive(Debug, Clone, Error)]
+pub enum DocsError {
+    #[error("Crate '{crate_name}' not found. {JSON_AVAILABILITY_NOTE}")]
+    CrateNotFound { crate_name: String },
+
+    #[error("Crate '{crate_name}' version {version} not found. {JSON_AVAILABILITY_NOTE}")]
+    VersionNotFound { crate_name: String, version: String },
+
+    #[error(
+        "Crate '{crate_name}' does not have rustdoc JSON available. Docs.rs returned HTML \
+         instead. {JSON_AVAILABILITY_NOTE}"
+    )]
+    NoJsonAvailable { crate_name: String },
+
+    #[error(
+        "Unsupported rustdoc format version: {format_version}. Supported versions: 46, 48-{}",
+        LATEST_FORMAT_VERSION
+    )]
+    UnsupportedFormat { format_version: u32 },
+
+    /// An item, module or trait that isn't in the crate
+    #[error("{kind} '{path}' not found in crate")]
+    ItemNotFound { kind: &'static str, path: String },
+
+    /// A malformed path, query or cursor
+    #[error("{0}")]
+    InvalidArgument(String),
+
+    #[error("Failed to send request to {service}: {message}")]
+    Network {
+        service: &'static str,
+        message: String,
+    },
+
+    #[error("{service} returned HTTP {status} {reason}")]
+    UpstreamStatus {
+        service: &'static str,
+        status: u16,
+        reason: String,
+    },
+
+    #[error("Request cancelled by the client")]
+    Cancelled,
+}
+
+impl DocsError {
+    /// A failed request to an upstream service, with the causes reqwest wraps
+    /// its errors around, like DNS or TLS failures
+    pub fn network(service: &'static str, error: &reqwest::Error) -> Self {
+        let mut message = error.to_string();
+        let mut source = std::error::Error::source(error);
+        while let Some(cause) = source {
+            message.push_str(": ");
+            message.push_str(&cause.to_string());
+            source = cause.source();
+        }
+        DocsError::Network { service, message }
+    }
+
+    /// An unsuccessful HTTP response from an upstream service
+    pub fn upstream_status(service: &'static str, status: reqwest::StatusCode) -> Self {
+        DocsError::UpstreamStatus {
+            service,
+            status: status.as_u16(),
+            reason: status.canonical_reason().unwrap_or("Unknown").to_string(),
+        }
+    }
+
+    /// Stable code identifying the kind of failure
+    pub fn code(&self) -> &'static str {
+        match self {
+            DocsError::CrateNotFound { .. } => "crate_not_found",
+            DocsError::VersionNotFound { .. } => "version_not_found",
+            DocsError::NoJsonAvailable { .. } => "no_json_available",
+            DocsError::UnsupportedFormat { .. } => "unsupported_format",
+            DocsError::ItemNotFound { .. } => "item_not_found",
+            DocsError::InvalidArgument(_) => "invalid_argument",
+            DocsError::Network { .. } => "network_error",
+            DocsError::UpstreamStatus { .. } => "upstream_http_error",
+            DocsError::Cancelled => "cancelled",
+        }
+    }
+
+    /// Find the typed error anywhere in an error's chain of causes
+    pub fn find(error: &anyhow::Error) -> Option<&DocsError> {
+        error.chain().find_map(|cause| cause.downcast_ref())
+    }
+}
@@ -0,0 +1,239 @@
+use std::future::IntoFuture;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use axum::extract::{Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use rmcp::transport::sse_server::{SseServer, SseServerConfig};
+use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
+use rmcp::transport::{StreamableHttpServerConfig, StreamableHttpService};
+use tokio_util::sync::CancellationToken;
+
+use crate::server::DocsRsServer;
+
+/// Endpoint of the streamable HTTP transport
+const MCP_PATH: &str = "/mcp";
+
+/// Endpoints of the legacy HTTP+SSE transport, for clients without streamable HTTP
+const SSE_PATH: &str = "/sse";
+const SSE_POST_PATH: &str = "/message";
+
+/// Interval between keep-alive pings on open SSE streams
+const SSE_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// How long requests in progress get to finish once shutdown starts
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// Serve MCP over HTTP until SIGTERM or Ctrl-C.
+///
+/// Every session is handled by a clone of `server`, so they all share its
+/// HTTP client, fetcher and caches. On shutdown the open sessions are closed
+/// and requests still in progress are given the chance to finish.
+///
+/// Requests a browser sends carry an `Origin` header, and only those from
+/// localhost or one of `allowed_origins` are served, so a web page can't use
+/// DNS rebinding to reach the server through the user's browser. Clients
+/// outside a browser send no origin and aren't affected. The server listens on
+/// loopback unless given another address; exposing it to other machines takes
+/// binding to a public address, e.g. `0.0.0.0:8080`, and allowing the origins
+/// of any browser-based clients that will connect to it.
+pub async fn serve(
+    server: DocsRsServer,
+    addr: SocketAddr,
+    allowed_origins: Vec<String>,
+) -> Result<()> {
+    let ct = CancellationToken::new();
+
+    let sessions = Arc::new(LocalSessionManager::default());
+    let streamable = {
+        let server = server.clone();
+        StreamableHttpService::new(
+            move || Ok(server.clone()),
+            Arc::clone(&sessions),
+            StreamableHttpServerConfig {
+                sse_keep_alive: Some(SSE_KEEP_ALIVE),
+                stateful_mode: true,
+            },
+        )
+    };
+
+    let (sse, sse_router) = SseServer::new(SseServerConfig {
+        bind: addr,
+        sse_path: SSE_PATH.to_string(),
+        post_path: SSE_POST_PATH.to_string(),
+        ct: ct.clone(),
+        sse_keep_alive: Some(SSE_KEEP_ALIVE),
+    });
+    sse.with_service(move || server.clone());
+
+    let router = axum::Router::new()
+        .nest_service(MCP_PATH, streamable)
+        .merge(sse_router)
+        .layer(middleware::from_fn_with_state(
+            Arc::new(allowed_origins),
+            check_origin,
+        ));
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind to {}", addr))?;
+    tracing::info!(
+        "Serving MCP on http://{}{} (SSE fallback on {})",
+        listener.local_addr()?,
+        MCP_PATH,
+        SSE_PATH
+    );
+    if !addr.ip().is_loopback() {
+        tracing::warn!(
+            "Listening on {}, which is reachable from other machines; the server has no \
+             authentication of its own",
+            addr
+        );
+    }
+
+    let mut http = tokio::spawn(
+        axum::serve(listener, router)
+            .with_graceful_shutdown(ct.clone().cancelled_owned())
+            .into_future(),
+    );
+    tokio::select! {
+        result = &mut http => return result?.context("HTTP server failed"),
+        _ = shutdown_signal() => {}
+    }
+
+    tracing::info!("Shutting down, closing open sessions");
+    ct.cancel();
+    for (id, session) in sessions.sessions.write().await.drain() {
+        if let Err(e) = session.close().await {
+            tracing::debug!("Failed to close session {}: {}", id, e);
+        }
+    }
+
+    // SSE streams stay open until their clients disconnect, so they are only
+    // waited on for a while
+    match tokio::time::timeout(SHUTDOWN_GRACE_PERIOD, http).await {
+        Ok(result) => result?.context("HTTP server failed"),
+        Err(_) => {
+            tracing::warn!(
+                "Connections still open after {:?}, stopping anyway",
+                SHUTDOWN_GRACE_PERIOD
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Reject requests from browser origins other than localhost and the allowed ones
+async fn check_origin(
+    State(allowed_origins): State<Arc<Vec<String>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(origin) = request.headers().get(header::ORIGIN) else {
+        return next.run(request).await;
+    };
+    match origin.to_str() {
+        Ok(origin) if is_allowed_origin(origin, &allowed_origins) => next.run(request).await,
+        _ => {
+            tracing::warn!("Rejecting request from origin {:?}", origin);
+            (StatusCode::FORBIDDEN, "Origin not allowed").into_response()
+        }
+    }
+}
+
+/// Whether an origin is on localhost or in the allowed list
+fn is_allowed_origin(origin: &str, allowed_origins: &[String]) -> bool {
+    let origin = origin.trim_end_matches('/');
+    if allowed_origins
+        .iter()
+        .any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+    {
+        return true;
+    }
+
+    let Some(authority) = origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+    else {
+        return false;
+    };
+    let host = match authority.strip_prefix('[') {
+        Some(ipv6) => match ipv6.split_once(']') {
+            Some((host, port)) if port.is_empty() || port.starts_with(':') => host,
+            _ => return false,
+        },
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    host.eq_ignore_ascii_case("localhost") || host == "127.0.0.1" || host == "::1"
+}
+
+/// Resolve once the process is asked to stop, by SIGTERM or Ctrl-C
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn localhost_origins_are_allowed() {
+        for origin in [
+            "http://localhost",
+            "http://localhost:3000",
+            "https://LOCALHOST:8443/",
+            "http://127.0.0.1:8080",
+            "http://[::1]:8080",
+        ] {
+            assert!(is_allowed_origin(origin, &[]), "{}", origin);
+        }
+    }
+
+    #[test]
+    fn other_origins_need_allowing() {
+        for origin in [
+            "http://evil.example",
+            "http://localhost.evil.example",
+            "http://127.0.0.1.evil.example",
+            "http://[::1].evil.example",
+            "null",
+            "file://",
+        ] {
+            assert!(!is_allowed_origin(origin, &[]), "{}", origin);
+        }
+
+        let allowed = ["https://app.example.com/".to_string()];
+        assert!(is_allowed_origin("https://app.example.com", &allowed));
+        assert!(!is_allowed_origin("http://app.example.com", &allowed));
+    }
+}
@@ -1,23 +1,134 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{error::ErrorKind, Args, CommandFactory, Parser, Subcommand};
 use rmcp::{transport::stdio, ServiceExt};
 use tracing_subscriber::{self, EnvFilter};
 
+mod completion;
+mod crate_cache;
+mod disk_cache;
 mod docs_fetcher;
+mod error;
+mod http;
+mod progress;
+mod prompts;
+mod resources;
 mod rustdoc_parser;
 mod server;
 mod tools;
 
-use crate::server::DocsRsServer;
+use crate::crate_cache::CrateCacheConfig;
+use crate::disk_cache::DiskCacheConfig;
+use crate::server::{DocsRsServer, ServerConfig};
 
 #[derive(Parser)]
 #[command(name = "docsrs-mcp")]
 #[command(about = "MCP server for accessing Rust crate documentation via docs.rs", long_about = None)]
 struct Cli {
+    #[command(flatten)]
+    cache: CacheArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+#[derive(Args)]
+struct CacheArgs {
+    /// Directory for the on-disk rustdoc JSON cache (default: $XDG_CACHE_HOME/docsrs-mcp)
+    #[arg(long, global = true)]
+    cache_dir: Option<PathBuf>,
+
+    /// Maximum size of the on-disk cache in megabytes
+    #[arg(long, global = true, default_value = "1024")]
+    cache_size_mb: u64,
+
+    /// Seconds before cached "latest" and semver-range lookups are refetched
+    #[arg(long, global = true, default_value = "3600")]
+    cache_ttl: u64,
+
+    /// Disable the on-disk cache
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    /// Memory budget in megabytes for parsed crates, measured by JSON size
+    #[arg(long, global = true, default_value = "512")]
+    memory_cache_mb: usize,
+}
+
+impl CacheArgs {
+    fn into_config(self) -> ServerConfig {
+        let disk_cache = if self.no_cache {
+            None
+        } else {
+            match self.cache_dir.or_else(DiskCacheConfig::default_dir) {
+                Some(dir) => Some(DiskCacheConfig {
+                    dir,
+                    max_bytes: self.cache_size_mb * 1024 * 1024,
+                    ttl: Duration::from_secs(self.cache_ttl),
+                }),
+                None => {
+                    tracing::warn!("No cache directory available, disk cache disabled");
+                    None
+                }
+            }
+        };
+
+        let crate_cache = CrateCacheConfig {
+            max_bytes: self.memory_cache_mb * 1024 * 1024,
+            ttl: Duration::from_secs(self.cache_ttl),
+        };
+
+        ServerConfig {
+            disk_cache,
+            crate_cache,
+        }
+    }
+}
+
+#[derive(Args)]
+struct OutputArgs {
+    /// Maximum length of the output in characters
+    #[arg(long, global = true)]
+    max_chars: Option<usize>,
+
+    /// Maximum length of the output in tokens, estimated at 4 characters per token
+    #[arg(long, global = true)]
+    max_tokens: Option<usize>,
+
+    /// Continuation cursor from a previous truncated output, to print its next chunk
+    #[arg(long, global = true)]
+    continuation: Option<String>,
+
+    /// Print the structured result as JSON instead of Markdown (lookup-crate,
+    /// lookup-item and search only, other commands reject it)
+    #[arg(long, global = true)]
+    json: bool,
+}
+
+impl OutputArgs {
+    fn format(&self) -> tools::output::OutputFormat {
+        if self.json {
+            tools::output::OutputFormat::Json
+        } else {
+            tools::output::OutputFormat::Markdown
+        }
+    }
+
+    fn into_limit(self) -> tools::output::OutputLimit {
+        tools::output::OutputLimit {
+            max_chars: self.max_chars,
+            max_tokens: self.max_tokens,
+            continuation: self.continuation,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Look up documentation for a Rust crate
@@ -39,9 +150,109 @@ enum Commands {
         /// Name of the Rust crate
         crate_name: String,
 
-        /// Path to specific item (e.g., "struct.MyStruct" or "fn.my_function")
+        /// Path to specific item (e.g., "MyStruct", "module::MyStruct::method", "struct.MyStruct" or "fn.my_function")
         item_path: String,
 
+        /// Only show the code examples from the item's documentation
+        #[arg(short, long)]
+        examples: bool,
+
+        /// Specific version or semver range
+        #[arg(short, long)]
+        version: Option<String>,
+
+        /// Target platform
+        #[arg(short, long)]
+        target: Option<String>,
+    },
+
+    /// List the types in a crate that implement a trait
+    FindImplementors {
+        /// Name of the Rust crate
+        crate_name: String,
+
+        /// Path to the trait (e.g., "Service" or "serde::Serialize")
+        trait_path: String,
+
+        /// Specific version or semver range
+        #[arg(short, long)]
+        version: Option<String>,
+
+        /// Target platform
+        #[arg(short, long)]
+        target: Option<String>,
+    },
+
+    /// List the public items of a module, grouped by kind
+    ListModule {
+        /// Name of the Rust crate
+        crate_name: String,
+
+        /// Path to the module (e.g., "sync" or "tokio::sync"), omit for the crate root
+        module_path: Option<String>,
+
+        /// How many levels of submodules to include
+        #[arg(short, long, default_value = "1")]
+        depth: usize,
+
+        /// Cursor returned by a previous listing, to fetch the next page
+        #[arg(short, long)]
+        cursor: Option<String>,
+
+        /// Maximum number of items per page
+        #[arg(short, long, default_value = "100")]
+        limit: usize,
+
+        /// Specific version or semver range
+        #[arg(short, long)]
+        version: Option<String>,
+
+        /// Target platform
+        #[arg(short, long)]
+        target: Option<String>,
+    },
+
+    /// Search the items of a crate by name, path and documentation
+    SearchItems {
+        /// Name of the Rust crate
+        crate_name: String,
+
+        /// Words to search for
+        query: String,
+
+        /// Only return items of this kind (e.g., "struct", "fn", "trait")
+        #[arg(short, long)]
+        kind: Option<String>,
+
+        /// Only return items under this module path
+        #[arg(short, long)]
+        module: Option<String>,
+
+        /// Maximum number of results to return
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+
+        /// Specific version or semver range
+        #[arg(short, long)]
+        version: Option<String>,
+
+        /// Target platform
+        #[arg(short, long)]
+        target: Option<String>,
+    },
+
+    /// Find functions and methods in a crate by type signature
+    SearchByType {
+        /// Name of the Rust crate
+        crate_name: String,
+
+        /// Type signature, e.g. "&str -> Result<Url, _>"
+        query: String,
+
+        /// Maximum number of results to return
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+
         /// Specific version or semver range
         #[arg(short, long)]
         version: Option<String>,
@@ -62,7 +273,29 @@ enum Commands {
     },
 
     /// Run as MCP server (default behavior)
-    Serve,
+    Serve {
+        /// Serve over streamable HTTP, with an SSE fallback, instead of over
+        /// stdio. Listens on 127.0.0.1:8080 unless given another address; to
+        /// accept connections from other machines, bind to e.g. 0.0.0.0:8080
+        /// and allow the origins of any browser-based clients
+        #[arg(long, num_args = 0..=1, default_missing_value = "127.0.0.1:8080")]
+        http: Option<SocketAddr>,
+
+        /// Origin allowed to make requests from a browser besides localhost
+        /// (e.g., "https://app.example.com"), can be given several times
+        #[arg(long = "allow-origin", requires = "http")]
+        allow_origins: Vec<String>,
+    },
+}
+
+impl Commands {
+    /// Whether the command can print its result as JSON
+    fn supports_json(&self) -> bool {
+        matches!(
+            self,
+            Commands::LookupCrate { .. } | Commands::LookupItem { .. } | Commands::Search { .. }
+        )
+    }
 }
 
 #[tokio::main]
@@ -75,6 +308,18 @@ async fn main() -> Result<()> {
         .init();
 
     let cli = Cli::parse();
+    if cli.output.json && !cli.command.as_ref().is_some_and(Commands::supports_json) {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--json is only supported by lookup-crate, lookup-item and search",
+            )
+            .exit();
+    }
+
+    let config = cli.cache.into_config();
+    let output_format = cli.output.format();
+    let output = cli.output.into_limit();
 
     match cli.command {
         Some(Commands::LookupCrate {
@@ -85,16 +330,18 @@ async fn main() -> Result<()> {
             // Test lookup_crate_docs tool
             tracing::info!("Testing lookup_crate_docs tool");
 
-            let server = DocsRsServer::new();
+            let server = DocsRsServer::new(config);
             let params = tools::lookup_crate::LookupCrateParams {
                 crate_name,
                 version,
                 target,
+                output_format,
+                output,
             };
 
-            match tools::lookup_crate::handle(&server.fetcher, params).await {
+            match tools::lookup_crate::handle(&server.crates, params).await {
                 Ok(content) => {
-                    println!("{}", content);
+                    println!("{}", content.text);
                 }
                 Err(e) => {
                     eprintln!("Error: {}", e);
@@ -106,21 +353,160 @@ async fn main() -> Result<()> {
         Some(Commands::LookupItem {
             crate_name,
             item_path,
+            examples,
             version,
             target,
         }) => {
             // Test lookup_item_docs tool
             tracing::info!("Testing lookup_item_docs tool");
 
-            let server = DocsRsServer::new();
+            let server = DocsRsServer::new(config);
+            let mode = if examples {
+                tools::lookup_item::LookupMode::Examples
+            } else {
+                tools::lookup_item::LookupMode::Docs
+            };
             let params = tools::lookup_item::LookupItemParams {
                 crate_name,
                 item_path,
+                mode,
                 version,
                 target,
+                output_format,
+                output,
             };
 
-            match tools::lookup_item::handle(&server.fetcher, params).await {
+            match tools::lookup_item::handle(&server.crates, &server.client, params).await {
+                Ok(content) => {
+                    println!("{}", content.text);
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Some(Commands::FindImplementors {
+            crate_name,
+            trait_path,
+            version,
+            target,
+        }) => {
+            // Test find_implementors tool
+            tracing::info!("Testing find_implementors tool");
+
+            let server = DocsRsServer::new(config);
+            let params = tools::find_implementors::FindImplementorsParams {
+                crate_name,
+                trait_path,
+                version,
+                target,
+                output,
+            };
+
+            match tools::find_implementors::handle(&server.crates, params).await {
+                Ok(content) => {
+                    println!("{}", content);
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Some(Commands::ListModule {
+            crate_name,
+            module_path,
+            depth,
+            cursor,
+            limit,
+            version,
+            target,
+        }) => {
+            // Test list_module tool
+            tracing::info!("Testing list_module tool");
+
+            let server = DocsRsServer::new(config);
+            let params = tools::list_module::ListModuleParams {
+                crate_name,
+                module_path,
+                depth,
+                cursor,
+                limit,
+                version,
+                target,
+                output,
+            };
+
+            match tools::list_module::handle(&server.crates, params).await {
+                Ok(content) => {
+                    println!("{}", content);
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Some(Commands::SearchItems {
+            crate_name,
+            query,
+            kind,
+            module,
+            limit,
+            version,
+            target,
+        }) => {
+            // Test search_items tool
+            tracing::info!("Testing search_items tool");
+
+            let server = DocsRsServer::new(config);
+            let params = tools::search_items::SearchItemsParams {
+                crate_name,
+                query,
+                kind,
+                module,
+                limit,
+                version,
+                target,
+                output,
+            };
+
+            match tools::search_items::handle(&server.crates, params).await {
+                Ok(content) => {
+                    println!("{}", content);
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Some(Commands::SearchByType {
+            crate_name,
+            query,
+            limit,
+            version,
+            target,
+        }) => {
+            // Test search_by_type tool
+            tracing::info!("Testing search_by_type tool");
+
+            let server = DocsRsServer::new(config);
+            let params = tools::search_by_type::SearchByTypeParams {
+                crate_name,
+                query,
+                limit,
+                version,
+                target,
+                output,
+            };
+
+            match tools::search_by_type::handle(&server.crates, params).await {
                 Ok(content) => {
                     println!("{}", content);
                 }
@@ -135,12 +521,17 @@ async fn main() -> Result<()> {
             // Test search_crates tool
             tracing::info!("Testing search_crates tool");
 
-            let server = DocsRsServer::new();
-            let params = tools::search_crates::SearchCratesParams { query, limit };
+            let server = DocsRsServer::new(config);
+            let params = tools::search_crates::SearchCratesParams {
+                query,
+                limit,
+                output_format,
+                output,
+            };
 
             match tools::search_crates::handle(&server.client, params).await {
                 Ok(content) => {
-                    println!("{}", content);
+                    println!("{}", content.text);
                 }
                 Err(e) => {
                     eprintln!("Error: {}", e);
@@ -149,14 +540,27 @@ async fn main() -> Result<()> {
             }
         }
 
-        Some(Commands::Serve) | None => {
+        Some(Commands::Serve {
+            http: Some(addr),
+            allow_origins,
+        }) => {
+            tracing::info!("Starting docs.rs MCP server over HTTP");
+
+            // One server, and so one fetcher and set of caches, for all sessions
+            http::serve(DocsRsServer::new(config), addr, allow_origins).await?;
+        }
+
+        Some(Commands::Serve { http: None, .. }) | None => {
             // Run as MCP server (default behavior)
             tracing::info!("Starting docs.rs MCP server");
 
             // Create an instance of our docs.rs server
-            let service = DocsRsServer::new().serve(stdio()).await.inspect_err(|e| {
-                tracing::error!("Failed to start server: {:?}", e);
-            })?;
+            let service = DocsRsServer::new(config)
+                .serve(stdio())
+                .await
+                .inspect_err(|e| {
+                    tracing::error!("Failed to start server: {:?}", e);
+                })?;
 
             // Wait for the server to complete
             service.waiting().await?;
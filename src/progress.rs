@@ -0,0 +1,69 @@
+use std::future::Future;
+
+use anyhow::Result;
+use rmcp::model::{ProgressNotificationParam, ProgressToken};
+use rmcp::service::RequestContext;
+use rmcp::{Peer, RoleServer};
+
+use crate::error::DocsError;
+
+tokio::task_local! {
+    /// Progress reporter of the request being handled on this task
+    static REPORTER: ProgressReporter;
+}
+
+/// Sends progress notifications to the client of a request that asked for them
+#[derive(Clone)]
+pub struct ProgressReporter {
+    peer: Peer<RoleServer>,
+    token: ProgressToken,
+}
+
+impl ProgressReporter {
+    /// The reporter of the request being handled, if its client sent a progress token
+    pub fn current() -> Option<Self> {
+        REPORTER.try_with(Clone::clone).ok()
+    }
+
+    pub async fn report(&self, progress: u64, total: Option<u64>, message: String) {
+        let notification = ProgressNotificationParam {
+            progress_token: self.token.clone(),
+            progress: progress as f64,
+            total: total.map(|total| total as f64),
+            message: Some(message),
+        };
+        if let Err(e) = self.peer.notify_progress(notification).await {
+            tracing::debug!("Failed to send progress notification: {}", e);
+        }
+    }
+}
+
+/// Run a request handler for a client. Downloads it starts report progress
+/// when the request carries a progress token, and it is dropped as soon as the
+/// client cancels the request, which aborts downloads and parses nobody else is
+/// waiting on.
+pub async fn track<T>(
+    context: &RequestContext<RoleServer>,
+    handler: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let handler = async {
+        match context.meta.get_progress_token() {
+            Some(token) => {
+                let reporter = ProgressReporter {
+                    peer: context.peer.clone(),
+                    token,
+                };
+                REPORTER.scope(reporter, handler).await
+            }
+            None => handler.await,
+        }
+    };
+
+    tokio::select! {
+        result = handler => result,
+        _ = context.ct.cancelled() => {
+            tracing::info!("Request {} cancelled by the client", context.id);
+            Err(DocsError::Cancelled.into())
+        }
+    }
+}
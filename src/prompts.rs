@@ -0,0 +1,303 @@
+use anyhow::Result;
+use futures::future::join_all;
+use reqwest::Client;
+use rmcp::model::{ErrorData as McpError, GetPromptResult, PromptMessage, PromptMessageRole};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::crate_cache::CrateCache;
+use crate::error::DocsError;
+use crate::resources;
+use crate::rustdoc_parser;
+use crate::tools::output::{OutputFormat, OutputLimit};
+use crate::tools::{find_implementors, lookup_crate, lookup_item, search_crates};
+
+/// Crates found by a search whose overviews are included when picking a crate
+const CANDIDATE_OVERVIEWS: usize = 3;
+
+/// Size limit for each candidate's overview, so the prompt stays manageable
+const CANDIDATE_OVERVIEW_CHARS: usize = 6000;
+
+/// Crates listed when picking a crate for a task
+const CANDIDATE_SEARCH_LIMIT: usize = 10;
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ExplainCrateParams {
+    /// Name of the Rust crate to explain
+    #[serde(rename = "crateName")]
+    pub crate_name: String,
+
+    /// Specific version or semver range (default: latest)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct UseTraitParams {
+    /// Name of the Rust crate the trait is in
+    #[serde(rename = "crateName")]
+    pub crate_name: String,
+
+    /// Path to the trait (e.g., "Service" or "io::AsyncRead")
+    #[serde(rename = "traitPath")]
+    pub trait_path: String,
+
+    /// Specific version or semver range (default: latest)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct MigrateVersionParams {
+    /// Name of the Rust crate
+    #[serde(rename = "crateName")]
+    pub crate_name: String,
+
+    /// Version currently in use (e.g., "0.11.0")
+    #[serde(rename = "fromVersion")]
+    pub from_version: String,
+
+    /// Version to migrate to (default: latest)
+    #[serde(rename = "toVersion", default, skip_serializing_if = "Option::is_none")]
+    pub to_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct PickCrateParams {
+    /// What the crate is needed for (e.g., "parse command line arguments")
+    pub task: String,
+}
+
+/// A plain text user message
+fn user_text(text: String) -> PromptMessage {
+    PromptMessage::new_text(PromptMessageRole::User, text)
+}
+
+/// A user message carrying documentation, headed by the URI of the resource
+/// it can be read again from.
+///
+/// This is text rather than an embedded resource, since rmcp serializes those
+/// in prompts with an extra level of nesting that clients don't understand.
+fn embedded_docs(uri: String, text: String) -> PromptMessage {
+    user_text(format!("Documentation from {}:\n\n{}", uri, text))
+}
+
+async fn crate_overview(
+    crates: &CrateCache,
+    crate_name: &str,
+    version: Option<&str>,
+    output: OutputLimit,
+) -> Result<String> {
+    let params = lookup_crate::LookupCrateParams {
+        crate_name: crate_name.to_string(),
+        version: version.map(str::to_string),
+        target: None,
+        output_format: OutputFormat::Markdown,
+        output,
+    };
+    Ok(lookup_crate::handle(crates, params).await?.text)
+}
+
+/// Explain a crate's core API from its overview
+pub async fn explain_crate(
+    crates: &CrateCache,
+    params: ExplainCrateParams,
+) -> Result<GetPromptResult> {
+    let version = params.version.as_deref();
+    let overview =
+        crate_overview(crates, &params.crate_name, version, OutputLimit::default()).await?;
+
+    Ok(GetPromptResult {
+        description: Some(format!("Explain the core API of {}", params.crate_name)),
+        messages: vec![
+            user_text(format!(
+                "Explain the core API of the `{}` Rust crate. Cover its main types, traits and \
+                 functions, how they fit together, and the typical way to use them, with a short \
+                 example. Base the explanation on the documentation below, and look up items it \
+                 doesn't cover with the docs.rs tools.",
+                params.crate_name
+            )),
+            embedded_docs(
+                resources::docs_uri(&params.crate_name, version.unwrap_or("latest"), None),
+                overview,
+            ),
+        ],
+    })
+}
+
+/// Explain how to use a trait from its docs and implementors
+pub async fn use_trait(
+    crates: &CrateCache,
+    client: &Client,
+    params: UseTraitParams,
+) -> Result<GetPromptResult> {
+    let item_params = lookup_item::LookupItemParams {
+        crate_name: params.crate_name.clone(),
+        item_path: params.trait_path.clone(),
+        mode: lookup_item::LookupMode::Docs,
+        version: params.version.clone(),
+        target: None,
+        output_format: OutputFormat::Markdown,
+        output: OutputLimit::default(),
+    };
+    let trait_docs = lookup_item::handle(crates, client, item_params).await?.text;
+
+    let version = params.version.as_deref().unwrap_or("latest");
+    let mut messages = vec![
+        user_text(format!(
+            "How do I use the `{}` trait from the `{}` Rust crate? Explain what it is for, how to \
+             call its methods, how to implement it for my own types and which methods that \
+             requires, and which types already implement it. Include short examples, based on \
+             the documentation below.",
+            params.trait_path, params.crate_name
+        )),
+        embedded_docs(
+            resources::docs_uri(&params.crate_name, version, Some(&params.trait_path)),
+            trait_docs,
+        ),
+    ];
+
+    let implementors_params = find_implementors::FindImplementorsParams {
+        crate_name: params.crate_name.clone(),
+        trait_path: params.trait_path.clone(),
+        version: params.version.clone(),
+        target: None,
+        output: OutputLimit::default(),
+    };
+    // The trait's own docs are enough to go on if its impls can't be listed
+    match find_implementors::handle(crates, implementors_params).await {
+        Ok(implementors) => messages.push(user_text(implementors)),
+        Err(e) => tracing::warn!(
+            "Failed to list implementors of {}: {:#}",
+            params.trait_path,
+            e
+        ),
+    }
+
+    Ok(GetPromptResult {
+        description: Some(format!(
+            "How to use {} from {}",
+            params.trait_path, params.crate_name
+        )),
+        messages,
+    })
+}
+
+/// Guide a migration between two versions from the items added and removed
+/// and the crate overview at both versions
+pub async fn migrate_version(
+    crates: &CrateCache,
+    params: MigrateVersionParams,
+) -> Result<GetPromptResult> {
+    let from = params.from_version.as_str();
+    let to = params.to_version.as_deref();
+
+    let old = crates.get(&params.crate_name, Some(from), None).await?;
+    let new = crates.get(&params.crate_name, to, None).await?;
+    let changes = rustdoc_parser::compare_versions(&old, &new);
+
+    let old_overview = crate_overview(
+        crates,
+        &params.crate_name,
+        Some(from),
+        OutputLimit::default(),
+    )
+    .await?;
+    let new_overview =
+        crate_overview(crates, &params.crate_name, to, OutputLimit::default()).await?;
+
+    let to = new.crate_version.as_deref().or(to).unwrap_or("latest");
+    Ok(GetPromptResult {
+        description: Some(format!(
+            "Migrate {} from {} to {}",
+            params.crate_name, from, to
+        )),
+        messages: vec![
+            user_text(format!(
+                "Help me migrate code using the `{}` Rust crate from version {} to {}. Explain \
+                 the breaking changes, what replaces each removed item, and how to update \
+                 typical code, with before and after examples. The public items that were \
+                 removed and added are listed below, followed by the crate's documentation at \
+                 both versions. Check the crate's changelog for changes in behavior that don't \
+                 show up in its API.",
+                params.crate_name, from, to
+            )),
+            user_text(changes),
+            embedded_docs(
+                resources::docs_uri(&params.crate_name, from, None),
+                old_overview,
+            ),
+            embedded_docs(
+                resources::docs_uri(&params.crate_name, to, None),
+                new_overview,
+            ),
+        ],
+    })
+}
+
+/// Compare the crates found for a task, with the overviews of the top ones
+pub async fn pick_crate(
+    crates: &CrateCache,
+    client: &Client,
+    params: PickCrateParams,
+) -> Result<GetPromptResult> {
+    let results = search_crates::search(client, &params.task, CANDIDATE_SEARCH_LIMIT).await?;
+    let listing = search_crates::format_results(&params.task, &results);
+
+    let mut messages = vec![
+        user_text(format!(
+            "I need a Rust crate to {}. Compare the candidates below by how well they fit the \
+             task, API design, maturity and popularity, recommend one, and show how to get \
+             started with it. The crates.io search results come first, followed by the \
+             documentation of the top results.",
+            params.task
+        )),
+        user_text(listing),
+    ];
+
+    // Fetch the overviews concurrently; crates without rustdoc JSON are left out
+    let candidates = &results.crates[..results.crates.len().min(CANDIDATE_OVERVIEWS)];
+    let overviews = join_all(candidates.iter().map(|candidate| {
+        let output = OutputLimit {
+            max_chars: Some(CANDIDATE_OVERVIEW_CHARS),
+            ..OutputLimit::default()
+        };
+        crate_overview(crates, &candidate.name, Some(&candidate.version), output)
+    }))
+    .await;
+    for (candidate, overview) in candidates.iter().zip(overviews) {
+        match overview {
+            Ok(overview) => messages.push(embedded_docs(
+                resources::docs_uri(&candidate.name, &candidate.version, None),
+                overview,
+            )),
+            Err(e) => tracing::warn!("Failed to fetch docs for {}: {:#}", candidate.name, e),
+        }
+    }
+
+    Ok(GetPromptResult {
+        description: Some(format!("Pick a crate to {}", params.task)),
+        messages,
+    })
+}
+
+/// Turn a failure to build a prompt into a protocol error, reporting unknown
+/// crates, versions and items as invalid arguments
+pub fn prompt_error(error: anyhow::Error) -> McpError {
+    let typed = DocsError::find(&error);
+    let code = typed.map_or("internal_error", DocsError::code);
+    let data = Some(json!({ "errorCode": code }));
+    let message = format!("{:#}", error);
+
+    match typed {
+        Some(
+            DocsError::CrateNotFound { .. }
+            | DocsError::VersionNotFound { .. }
+            | DocsError::NoJsonAvailable { .. }
+            | DocsError::ItemNotFound { .. }
+            | DocsError::InvalidArgument(_),
+        ) => McpError::invalid_params(message, data),
+        _ => McpError::internal_error(message, data),
+    }
+}
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use reqwest::Client;
+use rmcp::model::{
+    AnnotateAble, ErrorData as McpError, RawResource, RawResourceTemplate, ReadResourceResult,
+    Resource, ResourceContents, ResourceTemplate,
+};
+use serde_json::json;
+
+use crate::crate_cache::CrateCache;
+use crate::error::DocsError;
+use crate::tools::output::{OutputFormat, OutputLimit};
+use crate::tools::{lookup_crate, lookup_item};
+
+const URI_SCHEME: &str = "docsrs://";
+
+const MARKDOWN: &str = "text/markdown";
+
+/// A documentation resource, addressed as `docsrs://{crate}/{version}` for a
+/// crate's overview or `docsrs://{crate}/{version}/{item_path}` for an item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocsUri {
+    pub crate_name: String,
+    /// Exact version or semver range; `None` for the latest release
+    pub version: Option<String>,
+    pub item_path: Option<String>,
+}
+
+impl DocsUri {
+    /// Parse a resource URI. Item paths may separate their segments with
+    /// `::` or `/`, and any part may be percent-encoded.
+    pub fn parse(uri: &str) -> Result<Self> {
+        let invalid = || DocsError::InvalidArgument(format!("Invalid resource URI '{}'", uri));
+
+        let rest = uri.strip_prefix(URI_SCHEME).ok_or_else(invalid)?;
+        let mut segments = rest
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| urlencoding::decode(segment).map(|decoded| decoded.into_owned()));
+
+        let crate_name = segments
+            .next()
+            .ok_or_else(invalid)?
+            .map_err(|_| invalid())?;
+        let version = segments
+            .next()
+            .transpose()
+            .map_err(|_| invalid())?
+            .filter(|version| version != "latest");
+        let item_path = segments
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?
+            .join("::");
+
+        Ok(Self {
+            crate_name,
+            version,
+            item_path: Some(item_path).filter(|path| !path.is_empty()),
+        })
+    }
+}
+
+/// The URI of a crate's overview at a version (or "latest"), or of one of its items
+pub fn docs_uri(crate_name: &str, version: &str, item_path: Option<&str>) -> String {
+    match item_path {
+        Some(item_path) => format!("{}{}/{}/{}", URI_SCHEME, crate_name, version, item_path),
+        None => format!("{}{}/{}", URI_SCHEME, crate_name, version),
+    }
+}
+
+/// The URI templates clients can fill in to read any crate or item
+pub fn templates() -> Vec<ResourceTemplate> {
+    vec![
+        RawResourceTemplate {
+            uri_template: "docsrs://{crate}/{version}".to_string(),
+            name: "crate-docs".to_string(),
+            title: Some("Rust crate documentation".to_string()),
+            description: Some(
+                "Overview of a crate from docs.rs: its root documentation and public items. \
+                 The version is an exact version, a semver range or \"latest\"."
+                    .to_string(),
+            ),
+            mime_type: Some(MARKDOWN.to_string()),
+        }
+        .no_annotation(),
+        RawResourceTemplate {
+            uri_template: "docsrs://{crate}/{version}/{item_path}".to_string(),
+            name: "item-docs".to_string(),
+            title: Some("Rust item documentation".to_string()),
+            description: Some(
+                "Documentation for an item of a crate, e.g. docsrs://tokio/latest/sync::Mutex"
+                    .to_string(),
+            ),
+            mime_type: Some(MARKDOWN.to_string()),
+        }
+        .no_annotation(),
+    ]
+}
+
+/// The crates fetched recently, as resources for their overviews
+pub fn recent_crates(crates: &CrateCache) -> Vec<Resource> {
+    crates
+        .recent()
+        .into_iter()
+        .map(|cached| {
+            let mut resource = RawResource::new(
+                docs_uri(&cached.crate_name, &cached.version, None),
+                format!("{} {}", cached.crate_name, cached.version),
+            );
+            resource.description =
+                Some(format!("Documentation for the {} crate", cached.crate_name));
+            resource.mime_type = Some(MARKDOWN.to_string());
+            resource.no_annotation()
+        })
+        .collect()
+}
+
+/// Read a documentation resource, rendered as Markdown the same way the
+/// lookup tools render it
+pub async fn read(crates: &CrateCache, client: &Client, uri: &str) -> Result<ReadResourceResult> {
+    let docs_uri = DocsUri::parse(uri)?;
+    tracing::info!("Reading resource {}", uri);
+
+    let output = match docs_uri.item_path {
+        Some(item_path) => {
+            let params = lookup_item::LookupItemParams {
+                crate_name: docs_uri.crate_name,
+                item_path,
+                mode: lookup_item::LookupMode::Docs,
+                version: docs_uri.version,
+                target: None,
+                output_format: OutputFormat::Markdown,
+                output: OutputLimit::default(),
+            };
+            lookup_item::handle(crates, client, params).await?
+        }
+        None => {
+            let params = lookup_crate::LookupCrateParams {
+                crate_name: docs_uri.crate_name,
+                version: docs_uri.version,
+                target: None,
+                output_format: OutputFormat::Markdown,
+                output: OutputLimit::default(),
+            };
+            lookup_crate::handle(crates, params).await?
+        }
+    };
+
+    Ok(ReadResourceResult {
+        contents: vec![ResourceContents::TextResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some(MARKDOWN.to_string()),
+            text: output.text,
+            meta: None,
+        }],
+    })
+}
+
+/// Turn a failed read into a protocol error, reporting missing crates and
+/// items as unknown resources
+pub fn read_error(uri: &str, error: anyhow::Error) -> McpError {
+    let typed = DocsError::find(&error);
+    let code = typed.map_or("internal_error", DocsError::code);
+    let data = Some(json!({ "uri": uri, "errorCode": code }));
+    let message = format!("{:#}", error);
+
+    match typed {
+        Some(
+            DocsError::CrateNotFound { .. }
+            | DocsError::VersionNotFound { .. }
+            | DocsError::NoJsonAvailable { .. }
+            | DocsError::ItemNotFound { .. },
+        ) => McpError::resource_not_found(message, data),
+        Some(DocsError::InvalidArgument(_)) => McpError::invalid_params(message, data),
+        _ => McpError::internal_error(message, data),
+    }
+}
@@ -0,0 +1,60 @@
+//! Comparing the public items of two versions of a crate.
+//!
+//! Items are matched by path and kind, so an item that moved shows up as
+//! removed from its old path and added at its new one. Only items with a
+//! page of their own are compared, not fields or methods.
+
+use std::collections::BTreeSet;
+
+use super::model::{Crate, Visibility};
+
+/// Most items listed per section, so large rewrites don't flood the output
+const MAX_LISTED: usize = 200;
+
+/// Path and kind name of every public item of the crate itself
+fn public_items(krate: &Crate) -> BTreeSet<(String, &'static str)> {
+    krate
+        .paths
+        .iter()
+        .filter(|(_, summary)| summary.crate_id == 0)
+        .filter(|(id, _)| {
+            krate.index.get(id).is_some_and(|item| {
+                matches!(item.visibility, Visibility::Public | Visibility::Default)
+            })
+        })
+        .map(|(_, summary)| (summary.path.join("::"), summary.kind.name()))
+        .collect()
+}
+
+/// List the public items removed and added between two versions of a crate
+pub fn compare_versions(old: &Crate, new: &Crate) -> String {
+    let old_items = public_items(old);
+    let new_items = public_items(new);
+
+    let mut sections = vec![format!(
+        "# API changes from {} to {}",
+        old.crate_version.as_deref().unwrap_or("unknown"),
+        new.crate_version.as_deref().unwrap_or("unknown")
+    )];
+
+    let removed: Vec<_> = old_items.difference(&new_items).collect();
+    let added: Vec<_> = new_items.difference(&old_items).collect();
+    if removed.is_empty() && added.is_empty() {
+        sections.push("\nNo public items were added or removed.".to_string());
+    }
+
+    for (title, items) in [("Removed", removed), ("Added", added)] {
+        if items.is_empty() {
+            continue;
+        }
+        sections.push(format!("\n## {} ({})", title, items.len()));
+        for (path, kind) in items.iter().take(MAX_LISTED) {
+            sections.push(format!("- {} `{}`", kind, path));
+        }
+        if items.len() > MAX_LISTED {
+            sections.push(format!("- ... and {} more", items.len() - MAX_LISTED));
+        }
+    }
+
+    sections.join("\n")
+}
@@ -0,0 +1,64 @@
+//! Completion of partially typed item paths.
+//!
+//! Candidates come from the crate's `paths` table, so they are the items
+//! rustdoc gives a page of their own. A docs.rs kind prefix like `struct.`
+//! narrows the candidates to that kind and is kept in the completions.
+
+use std::collections::BTreeSet;
+
+use super::model::Crate;
+use super::resolve;
+
+/// Item paths starting with a prefix, shortest first
+#[derive(Debug, Clone, Default)]
+pub struct PathCompletions {
+    pub values: Vec<String>,
+    /// Number of matching paths, including those past the limit
+    pub total: usize,
+}
+
+/// Complete an item path, matching the full path (without the crate name)
+/// or, for a single segment, the item's own name
+pub fn complete_item_path(krate: &Crate, prefix: &str, limit: usize) -> PathCompletions {
+    let prefix = prefix.trim();
+    let (kind_prefix, kinds, partial) = match prefix.split_once('.') {
+        Some((kind, rest)) => match resolve::kinds_for_prefix(kind) {
+            Some(kinds) => (&prefix[..kind.len() + 1], kinds, rest),
+            None => ("", &[][..], prefix),
+        },
+        None => ("", &[][..], prefix),
+    };
+    let partial = partial.trim_start_matches("::").to_lowercase();
+    let by_name = !partial.contains("::");
+
+    // Whole-path matches rank above name matches, then shorter paths first
+    let mut matches = BTreeSet::new();
+    for summary in krate.paths.values() {
+        if summary.crate_id != 0 || summary.path.len() < 2 {
+            continue;
+        }
+        if !kinds.is_empty() && !kinds.contains(&summary.kind) {
+            continue;
+        }
+
+        let path = summary.path[1..].join("::");
+        let name = summary.path[summary.path.len() - 1].to_lowercase();
+        let rank = if path.to_lowercase().starts_with(&partial) {
+            0
+        } else if by_name && name.starts_with(&partial) {
+            1
+        } else {
+            continue;
+        };
+        matches.insert((rank, summary.path.len(), path));
+    }
+
+    let total = matches.len();
+    let values = matches
+        .into_iter()
+        .take(limit)
+        .map(|(_, _, path)| format!("{}{}", kind_prefix, path))
+        .collect();
+
+    PathCompletions { values, total }
+}
@@ -0,0 +1,630 @@
+//! Conversion from `rustdoc_types` into the version-independent [model](super::model).
+//!
+//! The `rustdoc_types` crates for the supported format versions share the same
+//! shape for everything we convert, so the conversion is written once as a macro.
+//! Each version module brings its crate into scope as `rustdoc_types` and
+//! invokes [`impl_conversion!`], which defines `convert` in that module.
+//! Format version 54 replaced string attributes with a structured enum, which
+//! the `structured_attrs` form of the macro renders back into source text.
+//! Attribute variants and item kinds added in later versions are passed in by
+//! the versions that have them, so every match stays exhaustive.
+
+/// Define `convert(rustdoc_types::Crate) -> model::Crate` in the calling module.
+///
+/// The caller must have the `rustdoc_types` crate of its format version in
+/// scope under the name `rustdoc_types`. By default attributes are expected to
+/// be plain strings. Format versions 54 and later pass
+/// `structured_attrs: [Variant => "source", ...]`, listing the unit attribute
+/// variants that not all of those versions have, and optionally
+/// `extra_kinds: [Kind, ...]` for item kinds the model shares with only some
+/// versions.
+macro_rules! impl_conversion {
+    () => {
+        $crate::rustdoc_parser::convert::impl_conversion!(
+            @convert attrs: |attrs: Vec<String>| attrs,
+            kinds: []
+        );
+    };
+    (
+        structured_attrs: [$($variant:ident => $source:literal),* $(,)?]
+        $(, extra_kinds: [$($kind:ident),* $(,)?])? $(,)?
+    ) => {
+        $crate::rustdoc_parser::convert::impl_conversion!(
+            @convert attrs: convert_attrs,
+            kinds: [$($($kind),*)?]
+        );
+
+        fn convert_attrs(attrs: Vec<rustdoc_types::Attribute>) -> Vec<String> {
+            use rustdoc_types::Attribute as A;
+            attrs
+                .into_iter()
+                .map(|attr| match attr {
+                    A::NonExhaustive => "#[non_exhaustive]".to_string(),
+                    A::MustUse { reason: None } => "#[must_use]".to_string(),
+                    A::MustUse {
+                        reason: Some(reason),
+                    } => format!("#[must_use = {:?}]", reason),
+                    $(A::$variant => $source.to_string(),)*
+                    A::AutomaticallyDerived => "#[automatically_derived]".to_string(),
+                    A::NoMangle => "#[unsafe(no_mangle)]".to_string(),
+                    A::ExportName(name) => format!("#[unsafe(export_name = {:?})]", name),
+                    A::LinkSection(section) => format!("#[unsafe(link_section = {:?})]", section),
+                    A::Repr(repr) => convert_repr(repr),
+                    A::TargetFeature { enable } => {
+                        format!("#[target_feature(enable = {:?})]", enable.join(","))
+                    }
+                    A::Other(source) => source,
+                })
+                .collect()
+        }
+
+        /// Render `#[repr(..)]` with its parts in the order they're usually
+        /// written, e.g. `#[repr(C, u8, packed, align(4))]`
+        fn convert_repr(repr: rustdoc_types::AttributeRepr) -> String {
+            use rustdoc_types::ReprKind as K;
+            let kind = match repr.kind {
+                // The default, only written out when it's the whole attribute
+                K::Rust => None,
+                K::C => Some("C"),
+                K::Transparent => Some("transparent"),
+                K::Simd => Some("simd"),
+            };
+            let mut parts: Vec<String> = kind.map(str::to_string).into_iter().collect();
+            parts.extend(repr.int);
+            match repr.packed {
+                Some(1) => parts.push("packed".to_string()),
+                Some(packed) => parts.push(format!("packed({})", packed)),
+                None => {}
+            }
+            if let Some(align) = repr.align {
+                parts.push(format!("align({})", align));
+            }
+            if parts.is_empty() {
+                parts.push("Rust".to_string());
+            }
+            format!("#[repr({})]", parts.join(", "))
+        }
+    };
+    (@convert attrs: $convert_attrs:expr, kinds: [$($kind:ident),*]) => {
+        use $crate::rustdoc_parser::model;
+
+        /// Convert a deserialized crate into the version-independent model
+        pub fn convert(krate: rustdoc_types::Crate) -> model::Crate {
+            model::Crate {
+                root: convert_id(krate.root),
+                crate_version: krate.crate_version,
+                includes_private: krate.includes_private,
+                index: krate
+                    .index
+                    .into_iter()
+                    .map(|(id, item)| (convert_id(id), convert_item(item)))
+                    .collect(),
+                paths: krate
+                    .paths
+                    .into_iter()
+                    .map(|(id, summary)| (convert_id(id), convert_summary(summary)))
+                    .collect(),
+                external_crates: krate
+                    .external_crates
+                    .into_iter()
+                    .map(|(crate_id, external)| {
+                        (
+                            crate_id,
+                            model::ExternalCrate {
+                                name: external.name,
+                                html_root_url: external.html_root_url,
+                            },
+                        )
+                    })
+                    .collect(),
+                format_version: krate.format_version,
+                search_index: Default::default(),
+                item_paths: Default::default(),
+            }
+        }
+
+        fn convert_id(id: rustdoc_types::Id) -> model::Id {
+            model::Id(id.0)
+        }
+
+        fn convert_ids(ids: Vec<rustdoc_types::Id>) -> Vec<model::Id> {
+            ids.into_iter().map(convert_id).collect()
+        }
+
+        fn convert_summary(summary: rustdoc_types::ItemSummary) -> model::ItemSummary {
+            model::ItemSummary {
+                crate_id: summary.crate_id,
+                path: summary.path,
+                kind: convert_kind(summary.kind),
+            }
+        }
+
+        fn convert_kind(kind: rustdoc_types::ItemKind) -> model::ItemKind {
+            use rustdoc_types::ItemKind as K;
+            match kind {
+                K::Module => model::ItemKind::Module,
+                K::ExternCrate => model::ItemKind::ExternCrate,
+                K::Use => model::ItemKind::Use,
+                K::Struct => model::ItemKind::Struct,
+                K::StructField => model::ItemKind::StructField,
+                K::Union => model::ItemKind::Union,
+                K::Enum => model::ItemKind::Enum,
+                K::Variant => model::ItemKind::Variant,
+                K::Function => model::ItemKind::Function,
+                K::TypeAlias => model::ItemKind::TypeAlias,
+                K::Constant => model::ItemKind::Constant,
+                K::Trait => model::ItemKind::Trait,
+                K::TraitAlias => model::ItemKind::TraitAlias,
+                K::Impl => model::ItemKind::Impl,
+                K::Static => model::ItemKind::Static,
+                K::ExternType => model::ItemKind::ExternType,
+                K::Macro => model::ItemKind::Macro,
+                K::ProcAttribute => model::ItemKind::ProcAttribute,
+                K::ProcDerive => model::ItemKind::ProcDerive,
+                K::AssocConst => model::ItemKind::AssocConst,
+                K::AssocType => model::ItemKind::AssocType,
+                K::Primitive => model::ItemKind::Primitive,
+                K::Keyword => model::ItemKind::Keyword,
+                $(K::$kind => model::ItemKind::$kind,)*
+            }
+        }
+
+        /// Convert a single item, also used to decode items of newer format versions
+        pub(super) fn convert_item(item: rustdoc_types::Item) -> model::Item {
+            model::Item {
+                id: convert_id(item.id),
+                crate_id: item.crate_id,
+                name: item.name,
+                visibility: convert_visibility(item.visibility),
+                docs: item.docs,
+                links: item
+                    .links
+                    .into_iter()
+                    .map(|(text, id)| (text, convert_id(id)))
+                    .collect(),
+                attrs: ($convert_attrs)(item.attrs),
+                deprecation: item.deprecation.map(|deprecation| model::Deprecation {
+                    since: deprecation.since,
+                    note: deprecation.note,
+                }),
+                inner: convert_inner(item.inner),
+            }
+        }
+
+        fn convert_visibility(visibility: rustdoc_types::Visibility) -> model::Visibility {
+            match visibility {
+                rustdoc_types::Visibility::Public => model::Visibility::Public,
+                rustdoc_types::Visibility::Default => model::Visibility::Default,
+                rustdoc_types::Visibility::Crate => model::Visibility::Crate,
+                rustdoc_types::Visibility::Restricted { parent, path } => {
+                    model::Visibility::Restricted {
+                        parent: convert_id(parent),
+                        path,
+                    }
+                }
+            }
+        }
+
+        fn convert_inner(inner: rustdoc_types::ItemEnum) -> model::ItemEnum {
+            use rustdoc_types::ItemEnum as I;
+            match inner {
+                I::Module(module) => model::ItemEnum::Module(model::Module {
+                    is_crate: module.is_crate,
+                    items: convert_ids(module.items),
+                    is_stripped: module.is_stripped,
+                }),
+                I::ExternCrate { name, rename } => model::ItemEnum::ExternCrate { name, rename },
+                I::Use(import) => model::ItemEnum::Use(model::Use {
+                    source: import.source,
+                    name: import.name,
+                    id: import.id.map(convert_id),
+                    is_glob: import.is_glob,
+                }),
+                I::Union(union) => model::ItemEnum::Union(model::Union {
+                    generics: convert_generics(union.generics),
+                    has_stripped_fields: union.has_stripped_fields,
+                    fields: convert_ids(union.fields),
+                    impls: convert_ids(union.impls),
+                }),
+                I::Struct(struct_) => model::ItemEnum::Struct(model::Struct {
+                    kind: match struct_.kind {
+                        rustdoc_types::StructKind::Unit => model::StructKind::Unit,
+                        rustdoc_types::StructKind::Tuple(fields) => model::StructKind::Tuple(
+                            fields.into_iter().map(|f| f.map(convert_id)).collect(),
+                        ),
+                        rustdoc_types::StructKind::Plain {
+                            fields,
+                            has_stripped_fields,
+                        } => model::StructKind::Plain {
+                            fields: convert_ids(fields),
+                            has_stripped_fields,
+                        },
+                    },
+                    generics: convert_generics(struct_.generics),
+                    impls: convert_ids(struct_.impls),
+                }),
+                I::StructField(type_) => model::ItemEnum::StructField(convert_type(type_)),
+                I::Enum(enum_) => model::ItemEnum::Enum(model::Enum {
+                    generics: convert_generics(enum_.generics),
+                    has_stripped_variants: enum_.has_stripped_variants,
+                    variants: convert_ids(enum_.variants),
+                    impls: convert_ids(enum_.impls),
+                }),
+                I::Variant(variant) => model::ItemEnum::Variant(model::Variant {
+                    kind: match variant.kind {
+                        rustdoc_types::VariantKind::Plain => model::VariantKind::Plain,
+                        rustdoc_types::VariantKind::Tuple(fields) => model::VariantKind::Tuple(
+                            fields.into_iter().map(|f| f.map(convert_id)).collect(),
+                        ),
+                        rustdoc_types::VariantKind::Struct {
+                            fields,
+                            has_stripped_fields,
+                        } => model::VariantKind::Struct {
+                            fields: convert_ids(fields),
+                            has_stripped_fields,
+                        },
+                    },
+                    discriminant: variant.discriminant.map(|d| model::Discriminant {
+                        expr: d.expr,
+                        value: d.value,
+                    }),
+                }),
+                I::Function(function) => model::ItemEnum::Function(model::Function {
+                    sig: convert_signature(function.sig),
+                    generics: convert_generics(function.generics),
+                    header: convert_header(function.header),
+                    has_body: function.has_body,
+                }),
+                I::Trait(trait_) => model::ItemEnum::Trait(model::Trait {
+                    is_auto: trait_.is_auto,
+                    is_unsafe: trait_.is_unsafe,
+                    is_dyn_compatible: trait_.is_dyn_compatible,
+                    items: convert_ids(trait_.items),
+                    generics: convert_generics(trait_.generics),
+                    bounds: convert_bounds(trait_.bounds),
+                    implementations: convert_ids(trait_.implementations),
+                }),
+                I::TraitAlias(alias) => model::ItemEnum::TraitAlias(model::TraitAlias {
+                    generics: convert_generics(alias.generics),
+                    params: convert_bounds(alias.params),
+                }),
+                I::Impl(impl_) => model::ItemEnum::Impl(model::Impl {
+                    is_unsafe: impl_.is_unsafe,
+                    generics: convert_generics(impl_.generics),
+                    provided_trait_methods: impl_.provided_trait_methods,
+                    trait_: impl_.trait_.map(convert_path),
+                    for_: convert_type(impl_.for_),
+                    items: convert_ids(impl_.items),
+                    is_negative: impl_.is_negative,
+                    is_synthetic: impl_.is_synthetic,
+                    blanket_impl: impl_.blanket_impl.map(convert_type),
+                }),
+                I::TypeAlias(alias) => model::ItemEnum::TypeAlias(model::TypeAlias {
+                    type_: convert_type(alias.type_),
+                    generics: convert_generics(alias.generics),
+                }),
+                I::Constant { type_, const_ } => model::ItemEnum::Constant {
+                    type_: convert_type(type_),
+                    const_: convert_constant(const_),
+                },
+                I::Static(static_) => model::ItemEnum::Static(model::Static {
+                    type_: convert_type(static_.type_),
+                    is_mutable: static_.is_mutable,
+                    expr: static_.expr,
+                }),
+                I::ExternType => model::ItemEnum::ExternType,
+                I::Macro(source) => model::ItemEnum::Macro(source),
+                I::ProcMacro(proc_macro) => model::ItemEnum::ProcMacro(model::ProcMacro {
+                    kind: match proc_macro.kind {
+                        rustdoc_types::MacroKind::Bang => model::MacroKind::Bang,
+                        rustdoc_types::MacroKind::Attr => model::MacroKind::Attr,
+                        rustdoc_types::MacroKind::Derive => model::MacroKind::Derive,
+                    },
+                    helpers: proc_macro.helpers,
+                }),
+                I::Primitive(primitive) => model::ItemEnum::Primitive(model::Primitive {
+                    name: primitive.name,
+                    impls: convert_ids(primitive.impls),
+                }),
+                I::AssocConst { type_, value } => model::ItemEnum::AssocConst {
+                    type_: convert_type(type_),
+                    value,
+                },
+                I::AssocType {
+                    generics,
+                    bounds,
+                    type_,
+                } => model::ItemEnum::AssocType {
+                    generics: convert_generics(generics),
+                    bounds: convert_bounds(bounds),
+                    type_: type_.map(convert_type),
+                },
+            }
+        }
+
+        fn convert_signature(sig: rustdoc_types::FunctionSignature) -> model::FunctionSignature {
+            model::FunctionSignature {
+                inputs: sig
+                    .inputs
+                    .into_iter()
+                    .map(|(name, type_)| (name, convert_type(type_)))
+                    .collect(),
+                output: sig.output.map(convert_type),
+                is_c_variadic: sig.is_c_variadic,
+            }
+        }
+
+        fn convert_header(header: rustdoc_types::FunctionHeader) -> model::FunctionHeader {
+            model::FunctionHeader {
+                is_const: header.is_const,
+                is_unsafe: header.is_unsafe,
+                is_async: header.is_async,
+                abi: convert_abi(header.abi),
+            }
+        }
+
+        fn convert_abi(abi: rustdoc_types::Abi) -> Option<String> {
+            use rustdoc_types::Abi as A;
+            let (name, unwind) = match abi {
+                A::Rust => return None,
+                A::Other(name) => return Some(name),
+                A::C { unwind } => ("C", unwind),
+                A::Cdecl { unwind } => ("cdecl", unwind),
+                A::Stdcall { unwind } => ("stdcall", unwind),
+                A::Fastcall { unwind } => ("fastcall", unwind),
+                A::Aapcs { unwind } => ("aapcs", unwind),
+                A::Win64 { unwind } => ("win64", unwind),
+                A::SysV64 { unwind } => ("sysv64", unwind),
+                A::System { unwind } => ("system", unwind),
+            };
+            if unwind {
+                Some(format!("{}-unwind", name))
+            } else {
+                Some(name.to_string())
+            }
+        }
+
+        fn convert_generics(generics: rustdoc_types::Generics) -> model::Generics {
+            model::Generics {
+                params: convert_params(generics.params),
+                where_predicates: generics
+                    .where_predicates
+                    .into_iter()
+                    .map(convert_where_predicate)
+                    .collect(),
+            }
+        }
+
+        fn convert_params(params: Vec<rustdoc_types::GenericParamDef>) -> Vec<model::GenericParamDef> {
+            params
+                .into_iter()
+                .map(|param| model::GenericParamDef {
+                    name: param.name,
+                    kind: match param.kind {
+                        rustdoc_types::GenericParamDefKind::Lifetime { outlives } => {
+                            model::GenericParamDefKind::Lifetime { outlives }
+                        }
+                        rustdoc_types::GenericParamDefKind::Type {
+                            bounds,
+                            default,
+                            is_synthetic,
+                        } => model::GenericParamDefKind::Type {
+                            bounds: convert_bounds(bounds),
+                            default: default.map(convert_type),
+                            is_synthetic,
+                        },
+                        rustdoc_types::GenericParamDefKind::Const { type_, default } => {
+                            model::GenericParamDefKind::Const {
+                                type_: convert_type(type_),
+                                default,
+                            }
+                        }
+                    },
+                })
+                .collect()
+        }
+
+        fn convert_where_predicate(
+            predicate: rustdoc_types::WherePredicate,
+        ) -> model::WherePredicate {
+            match predicate {
+                rustdoc_types::WherePredicate::BoundPredicate {
+                    type_,
+                    bounds,
+                    generic_params,
+                } => model::WherePredicate::BoundPredicate {
+                    type_: convert_type(type_),
+                    bounds: convert_bounds(bounds),
+                    generic_params: convert_params(generic_params),
+                },
+                rustdoc_types::WherePredicate::LifetimePredicate { lifetime, outlives } => {
+                    model::WherePredicate::LifetimePredicate { lifetime, outlives }
+                }
+                rustdoc_types::WherePredicate::EqPredicate { lhs, rhs } => {
+                    model::WherePredicate::EqPredicate {
+                        lhs: convert_type(lhs),
+                        rhs: convert_term(rhs),
+                    }
+                }
+            }
+        }
+
+        fn convert_bounds(bounds: Vec<rustdoc_types::GenericBound>) -> Vec<model::GenericBound> {
+            bounds
+                .into_iter()
+                .map(|bound| match bound {
+                    rustdoc_types::GenericBound::TraitBound {
+                        trait_,
+                        generic_params,
+                        modifier,
+                    } => model::GenericBound::TraitBound {
+                        trait_: convert_path(trait_),
+                        generic_params: convert_params(generic_params),
+                        modifier: match modifier {
+                            rustdoc_types::TraitBoundModifier::None => {
+                                model::TraitBoundModifier::None
+                            }
+                            rustdoc_types::TraitBoundModifier::Maybe => {
+                                model::TraitBoundModifier::Maybe
+                            }
+                            rustdoc_types::TraitBoundModifier::MaybeConst => {
+                                model::TraitBoundModifier::MaybeConst
+                            }
+                        },
+                    },
+                    rustdoc_types::GenericBound::Outlives(lifetime) => {
+                        model::GenericBound::Outlives(lifetime)
+                    }
+                    rustdoc_types::GenericBound::Use(args) => model::GenericBound::Use(
+                        args.into_iter()
+                            .map(|arg| match arg {
+                                rustdoc_types::PreciseCapturingArg::Lifetime(name)
+                                | rustdoc_types::PreciseCapturingArg::Param(name) => name,
+                            })
+                            .collect(),
+                    ),
+                })
+                .collect()
+        }
+
+        fn convert_path(path: rustdoc_types::Path) -> model::Path {
+            model::Path {
+                path: path.path,
+                id: convert_id(path.id),
+                args: path.args.map(|args| Box::new(convert_generic_args(*args))),
+            }
+        }
+
+        fn convert_generic_args(args: rustdoc_types::GenericArgs) -> model::GenericArgs {
+            match args {
+                rustdoc_types::GenericArgs::AngleBracketed { args, constraints } => {
+                    model::GenericArgs::AngleBracketed {
+                        args: args
+                            .into_iter()
+                            .map(|arg| match arg {
+                                rustdoc_types::GenericArg::Lifetime(lifetime) => {
+                                    model::GenericArg::Lifetime(lifetime)
+                                }
+                                rustdoc_types::GenericArg::Type(type_) => {
+                                    model::GenericArg::Type(convert_type(type_))
+                                }
+                                rustdoc_types::GenericArg::Const(constant) => {
+                                    model::GenericArg::Const(convert_constant(constant))
+                                }
+                                rustdoc_types::GenericArg::Infer => model::GenericArg::Infer,
+                            })
+                            .collect(),
+                        constraints: constraints
+                            .into_iter()
+                            .map(|constraint| model::AssocItemConstraint {
+                                name: constraint.name,
+                                binding: match constraint.binding {
+                                    rustdoc_types::AssocItemConstraintKind::Equality(term) => {
+                                        model::AssocItemConstraintKind::Equality(convert_term(
+                                            term,
+                                        ))
+                                    }
+                                    rustdoc_types::AssocItemConstraintKind::Constraint(bounds) => {
+                                        model::AssocItemConstraintKind::Constraint(convert_bounds(
+                                            bounds,
+                                        ))
+                                    }
+                                },
+                            })
+                            .collect(),
+                    }
+                }
+                rustdoc_types::GenericArgs::Parenthesized { inputs, output } => {
+                    model::GenericArgs::Parenthesized {
+                        inputs: inputs.into_iter().map(convert_type).collect(),
+                        output: output.map(convert_type),
+                    }
+                }
+                // Return type notation is the only other form
+                #[allow(unreachable_patterns)]
+                _ => model::GenericArgs::ReturnTypeNotation,
+            }
+        }
+
+        fn convert_term(term: rustdoc_types::Term) -> model::Term {
+            match term {
+                rustdoc_types::Term::Type(type_) => model::Term::Type(convert_type(type_)),
+                rustdoc_types::Term::Constant(constant) => {
+                    model::Term::Constant(convert_constant(constant))
+                }
+            }
+        }
+
+        fn convert_constant(constant: rustdoc_types::Constant) -> model::Constant {
+            model::Constant {
+                expr: constant.expr,
+                value: constant.value,
+                is_literal: constant.is_literal,
+            }
+        }
+
+        fn convert_type(type_: rustdoc_types::Type) -> model::Type {
+            use rustdoc_types::Type as T;
+            match type_ {
+                T::ResolvedPath(path) => model::Type::ResolvedPath(convert_path(path)),
+                T::DynTrait(dyn_trait) => model::Type::DynTrait(model::DynTrait {
+                    traits: dyn_trait
+                        .traits
+                        .into_iter()
+                        .map(|poly| model::PolyTrait {
+                            trait_: convert_path(poly.trait_),
+                            generic_params: convert_params(poly.generic_params),
+                        })
+                        .collect(),
+                    lifetime: dyn_trait.lifetime,
+                }),
+                T::Generic(name) => model::Type::Generic(name),
+                T::Primitive(name) => model::Type::Primitive(name),
+                T::FunctionPointer(pointer) => {
+                    let pointer = *pointer;
+                    model::Type::FunctionPointer(Box::new(model::FunctionPointer {
+                        sig: convert_signature(pointer.sig),
+                        generic_params: convert_params(pointer.generic_params),
+                        header: convert_header(pointer.header),
+                    }))
+                }
+                T::Tuple(types) => model::Type::Tuple(types.into_iter().map(convert_type).collect()),
+                T::Slice(type_) => model::Type::Slice(Box::new(convert_type(*type_))),
+                T::Array { type_, len } => model::Type::Array {
+                    type_: Box::new(convert_type(*type_)),
+                    len,
+                },
+                T::Pat { type_, .. } => model::Type::Pat {
+                    type_: Box::new(convert_type(*type_)),
+                },
+                T::ImplTrait(bounds) => model::Type::ImplTrait(convert_bounds(bounds)),
+                T::Infer => model::Type::Infer,
+                T::RawPointer { is_mutable, type_ } => model::Type::RawPointer {
+                    is_mutable,
+                    type_: Box::new(convert_type(*type_)),
+                },
+                T::BorrowedRef {
+                    lifetime,
+                    is_mutable,
+                    type_,
+                } => model::Type::BorrowedRef {
+                    lifetime,
+                    is_mutable,
+                    type_: Box::new(convert_type(*type_)),
+                },
+                T::QualifiedPath {
+                    name,
+                    self_type,
+                    trait_,
+                    ..
+                } => model::Type::QualifiedPath {
+                    name,
+                    self_type: Box::new(convert_type(*self_type)),
+                    trait_: trait_.map(convert_path),
+                },
+            }
+        }
+    };
+}
+
+pub(super) use impl_conversion;
@@ -0,0 +1,316 @@
+//! Extracting code examples from documentation.
+//!
+//! Rust code blocks are pulled out of the docs of an item, its methods or
+//! children, and the module it lives in. Lines rustdoc hides from rendered
+//! examples (`# use foo::Bar;`) are dropped, while attributes like `no_run`
+//! or `should_panic` are kept since they say how the example behaves.
+
+use anyhow::Result;
+
+use crate::error::DocsError;
+
+use super::format::ambiguous_candidates;
+use super::model::{Crate, Id, Item, ItemEnum, ItemKind};
+use super::resolve::{self, Lookup, Resolution};
+use super::structured::ItemLookup;
+
+/// Code block attributes that rustdoc understands, besides `rust` itself
+const RUSTDOC_ATTRIBUTES: &[&str] = &[
+    "ignore",
+    "no_run",
+    "should_panic",
+    "compile_fail",
+    "test_harness",
+    "standalone_crate",
+];
+
+/// A code example and the item whose docs it was found in
+#[derive(Debug, Clone)]
+pub(super) struct Example {
+    pub(super) owner: Vec<String>,
+    /// Rustdoc attributes from the code block's info string, e.g. `no_run`
+    pub(super) attributes: Vec<String>,
+    pub(super) code: String,
+}
+
+/// The rustdoc attributes of a code block, or `None` if it isn't Rust
+fn rust_block_attributes(info: &str) -> Option<Vec<String>> {
+    let mut attributes = Vec::new();
+    for token in info
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+    {
+        if token == "rust" {
+            continue;
+        }
+        let known = RUSTDOC_ATTRIBUTES.contains(&token)
+            || token.starts_with("ignore-")
+            || token.starts_with("edition")
+            || is_error_code(token);
+        if !known {
+            return None;
+        }
+        attributes.push(token.to_string());
+    }
+    Some(attributes)
+}
+
+/// Whether a token is an error code like `E0277`, which `compile_fail`
+/// examples use to say which error they expect
+fn is_error_code(token: &str) -> bool {
+    token.len() == 5
+        && token.starts_with('E')
+        && token[1..].bytes().all(|byte| byte.is_ascii_digit())
+}
+
+/// Drop the lines rustdoc hides from examples, and unescape `##` lines
+fn strip_hidden_lines(code: &[&str]) -> String {
+    code.iter()
+        .filter_map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed == "#" || trimmed.starts_with("# ") {
+                None
+            } else if trimmed.starts_with("##") {
+                let indent = line.len() - trimmed.len();
+                Some(format!("{}{}", &line[..indent], &trimmed[1..]))
+            } else {
+                Some(line.to_string())
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Extract the fenced Rust code blocks from Markdown documentation
+fn extract_code_blocks(docs: &str) -> Vec<(Vec<String>, String)> {
+    let mut blocks = Vec::new();
+    let mut lines = docs.lines();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start();
+        let Some(fence) = ["```", "~~~"]
+            .into_iter()
+            .find(|fence| trimmed.starts_with(fence))
+        else {
+            continue;
+        };
+        let fence_len = trimmed.len() - trimmed.trim_start_matches(&fence[..1]).len();
+        let fence = &trimmed[..fence_len];
+        let info = &trimmed[fence_len..];
+
+        let mut code = Vec::new();
+        for line in lines.by_ref() {
+            if line.trim_start().starts_with(fence) {
+                break;
+            }
+            code.push(line);
+        }
+
+        if let Some(attributes) = rust_block_attributes(info) {
+            let code = strip_hidden_lines(&code);
+            if !code.trim().is_empty() {
+                blocks.push((attributes, code));
+            }
+        }
+    }
+
+    blocks
+}
+
+/// Items whose docs are searched for examples of an item: the item itself,
+/// then its methods, variants or, for modules, public children
+fn example_sources<'a>(
+    krate: &'a Crate,
+    item: &'a Item,
+    path: &[String],
+) -> Vec<(&'a Item, Vec<String>)> {
+    let mut sources = vec![(item, path.to_vec())];
+
+    let children: Vec<&Id> = match &item.inner {
+        ItemEnum::Struct(struct_data) => {
+            resolve::inherent_and_trait_items(krate, &struct_data.impls).collect()
+        }
+        ItemEnum::Union(union_data) => {
+            resolve::inherent_and_trait_items(krate, &union_data.impls).collect()
+        }
+        ItemEnum::Enum(enum_data) => enum_data
+            .variants
+            .iter()
+            .chain(resolve::inherent_and_trait_items(krate, &enum_data.impls))
+            .collect(),
+        ItemEnum::Primitive(primitive) => {
+            resolve::inherent_and_trait_items(krate, &primitive.impls).collect()
+        }
+        ItemEnum::Trait(trait_data) => trait_data.items.iter().collect(),
+        ItemEnum::Module(module) => module.items.iter().collect(),
+        _ => Vec::new(),
+    };
+
+    for child in children.into_iter().filter_map(|id| krate.index.get(id)) {
+        if let Some(name) = &child.name {
+            let mut child_path = path.to_vec();
+            child_path.push(name.clone());
+            sources.push((child, child_path));
+        }
+    }
+
+    sources
+}
+
+/// The module an item lives in, found by walking up its path
+fn parent_module<'a>(krate: &'a Crate, path: &[String]) -> Option<(&'a Item, Vec<String>)> {
+    (1..path.len()).rev().find_map(|len| {
+        let parent = &path[..len];
+        let (id, _) = krate
+            .paths
+            .iter()
+            .find(|(_, summary)| summary.kind == ItemKind::Module && summary.path == parent)?;
+        Some((krate.index.get(id)?, parent.to_vec()))
+    })
+}
+
+/// Collect the examples for an item, from its own docs, those of its
+/// methods or children, and those of its module
+pub(super) fn collect_examples(krate: &Crate, item: &Item, path: &[String]) -> Vec<Example> {
+    let mut sources = example_sources(krate, item, path);
+    if item.inner.kind() != ItemKind::Module {
+        // Re-exported items live in the module they're defined in
+        let defined_at = krate
+            .paths
+            .get(&item.id)
+            .map_or(path, |summary| summary.path.as_slice());
+        sources.extend(parent_module(krate, defined_at));
+    }
+
+    sources
+        .into_iter()
+        .flat_map(|(source, owner)| {
+            let docs = source.docs.as_deref().unwrap_or_default();
+            extract_code_blocks(docs)
+                .into_iter()
+                .map(move |(attributes, code)| Example {
+                    owner: owner.clone(),
+                    attributes,
+                    code,
+                })
+        })
+        .collect()
+}
+
+/// Format the code examples collected for a looked up item
+pub fn format_examples(item_path: &str, lookup: &Lookup, result: &ItemLookup) -> Result<String> {
+    let candidate = match lookup {
+        Lookup::Local(Resolution::Found(candidate)) => candidate,
+        Lookup::Local(Resolution::Ambiguous(_)) => {
+            return Ok(ambiguous_candidates(item_path, &result.candidates))
+        }
+        // Items of other crates have no docs here to take examples from
+        Lookup::External(_) => {
+            return Err(DocsError::ItemNotFound {
+                kind: "Item",
+                path: item_path.to_string(),
+            }
+            .into())
+        }
+    };
+
+    let path = candidate.path.join("::");
+    let examples = &result.examples;
+    if examples.is_empty() {
+        return Ok(format!("No code examples found in the docs of `{}`", path));
+    }
+
+    let mut sections = vec![format!(
+        "# Examples for `{}`\n\nFound {} example{}",
+        path,
+        examples.len(),
+        if examples.len() == 1 { "" } else { "s" }
+    )];
+    for example in examples {
+        let fence_info = std::iter::once("rust")
+            .chain(example.attributes.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(",");
+        sections.push(format!(
+            "\n## `{}`\n```{}\n{}\n```",
+            example.owner, fence_info, example.code
+        ));
+    }
+
+    Ok(sections.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rust_block_attributes_accepts_rustdoc_attributes() {
+        assert_eq!(rust_block_attributes(""), Some(vec![]));
+        assert_eq!(rust_block_attributes("rust"), Some(vec![]));
+        assert_eq!(
+            rust_block_attributes("rust,no_run"),
+            Some(vec!["no_run".to_string()])
+        );
+        assert_eq!(
+            rust_block_attributes("should_panic edition2021"),
+            Some(vec!["should_panic".to_string(), "edition2021".to_string()])
+        );
+        assert_eq!(
+            rust_block_attributes("ignore-windows"),
+            Some(vec!["ignore-windows".to_string()])
+        );
+    }
+
+    #[test]
+    fn rust_block_attributes_accepts_error_codes() {
+        assert_eq!(
+            rust_block_attributes("compile_fail,E0277"),
+            Some(vec!["compile_fail".to_string(), "E0277".to_string()])
+        );
+        assert_eq!(rust_block_attributes("compile_fail,E027"), None);
+        assert_eq!(rust_block_attributes("compile_fail,E02777"), None);
+        assert_eq!(rust_block_attributes("compile_fail,Eabcd"), None);
+    }
+
+    #[test]
+    fn rust_block_attributes_rejects_other_languages() {
+        assert_eq!(rust_block_attributes("text"), None);
+        assert_eq!(rust_block_attributes("toml"), None);
+        assert_eq!(rust_block_attributes("rust,text"), None);
+    }
+
+    #[test]
+    fn strip_hidden_lines_drops_hidden_lines_and_unescapes() {
+        let code = [
+            "# use std::io;",
+            "#",
+            "let x = 1;",
+            "    # let hidden = 2;",
+            "    ## [derive(Debug)]",
+            "#[derive(Clone)]",
+            "let s = \"# not hidden\";",
+        ];
+        assert_eq!(
+            strip_hidden_lines(&code),
+            "let x = 1;\n    # [derive(Debug)]\n#[derive(Clone)]\nlet s = \"# not hidden\";"
+        );
+    }
+
+    #[test]
+    fn extract_code_blocks_keeps_rust_blocks_only() {
+        let docs = "Intro\n\n```\n# fn main() {}\nlet a = 1;\n```\n\n```text\nnot rust\n```\n\n\
+                    ~~~compile_fail,E0382\nlet b = a;\n~~~\n\n````rust\n```\nnested\n````";
+        assert_eq!(
+            extract_code_blocks(docs),
+            vec![
+                (vec![], "let a = 1;".to_string()),
+                (
+                    vec!["compile_fail".to_string(), "E0382".to_string()],
+                    "let b = a;".to_string()
+                ),
+                (vec![], "```\nnested".to_string()),
+            ]
+        );
+    }
+}
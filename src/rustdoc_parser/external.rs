@@ -0,0 +1,168 @@
+//! Items defined in dependencies, which a crate's JSON only knows by path.
+//!
+//! `Crate::paths` records the kind and full path of every external item the
+//! crate refers to, and `Crate::external_crates` the docs URL of each
+//! dependency, which on docs.rs includes the exact version it was built with.
+
+use anyhow::Result;
+
+use super::model::{Crate, ItemKind};
+use super::reexport;
+use super::resolve::ItemQuery;
+
+/// Crates shipped with the toolchain, which have no rustdoc JSON on docs.rs
+const STD_CRATES: &[&str] = &["std", "core", "alloc", "proc_macro", "test"];
+
+/// An item defined in another crate
+#[derive(Debug, Clone)]
+pub struct ExternalItem {
+    /// Name of the crate on crates.io, e.g. `serde_json`
+    pub crate_name: String,
+    /// Version the crate was documented against, if its docs URL has one
+    pub version: Option<String>,
+    /// Full path of the item in its own crate
+    pub path: Vec<String>,
+    pub kind: ItemKind,
+    /// Path the item is re-exported under in the crate being browsed
+    pub exported_as: Option<Vec<String>>,
+    /// Link to the item's page in the dependency's docs
+    pub docs_url: Option<String>,
+}
+
+impl ExternalItem {
+    /// Whether the item comes from the standard library
+    pub fn is_std(&self) -> bool {
+        STD_CRATES.contains(&self.crate_name.as_str())
+    }
+}
+
+/// Find an item of another crate by the path it's re-exported under, or by
+/// its own path, where `http::Response` also matches `http::response::Response`
+pub(super) fn locate(krate: &Crate, item_path: &str) -> Result<Option<ExternalItem>> {
+    let query = ItemQuery::parse(item_path)?;
+
+    let reexported = reexport::reexports(krate)
+        .into_iter()
+        .filter(|(path, export)| export.item.is_none() && query.matches(path, export.kind))
+        .min_by_key(|(path, _)| path.len());
+    if let Some((exported_as, export)) = reexported {
+        let origin = export.origin.unwrap_or_default();
+        let crate_id = krate
+            .paths
+            .values()
+            .find(|summary| summary.crate_id != 0 && summary.path == origin)
+            .map(|summary| summary.crate_id);
+        return Ok(Some(external_item(
+            krate,
+            crate_id,
+            origin,
+            export.kind,
+            Some(exported_as),
+        )));
+    }
+
+    let found = krate
+        .paths
+        .values()
+        .filter(|summary| summary.crate_id != 0)
+        .filter(|summary| {
+            query.matches(&summary.path, summary.kind)
+                || (query.matches_kind(summary.kind)
+                    && summary.path.last() == query.segments.last()
+                    && is_subsequence(&query.segments, &summary.path))
+        })
+        .min_by(|a, b| a.path.len().cmp(&b.path.len()).then(a.path.cmp(&b.path)));
+
+    Ok(found.map(|summary| {
+        external_item(
+            krate,
+            Some(summary.crate_id),
+            summary.path.clone(),
+            summary.kind,
+            None,
+        )
+    }))
+}
+
+/// Whether every segment of `query` occurs in `path` in order, starting at the crate name
+fn is_subsequence(query: &[String], path: &[String]) -> bool {
+    if query.first() != path.first() {
+        return false;
+    }
+    let mut path = path.iter();
+    query.iter().all(|segment| path.any(|part| part == segment))
+}
+
+fn external_item(
+    krate: &Crate,
+    crate_id: Option<u32>,
+    path: Vec<String>,
+    kind: ItemKind,
+    exported_as: Option<Vec<String>>,
+) -> ExternalItem {
+    let external = crate_id.and_then(|id| krate.external_crates.get(&id));
+    let root_url = external.and_then(|external| external.html_root_url.as_deref());
+
+    // docs.rs links dependencies as `https://docs.rs/<crate>/<version>/`
+    let (docs_name, version) = match root_url.and_then(|url| url.strip_prefix("https://docs.rs/")) {
+        Some(rest) => {
+            let mut parts = rest.split('/');
+            let name = parts.next().filter(|name| !name.is_empty());
+            let version = parts
+                .next()
+                .filter(|version| !version.is_empty() && *version != "latest");
+            (name.map(str::to_string), version.map(str::to_string))
+        }
+        None => (None, None),
+    };
+
+    let crate_name = docs_name
+        .or_else(|| external.map(|external| external.name.clone()))
+        .or_else(|| path.first().cloned())
+        .unwrap_or_default();
+    let docs_url = root_url.and_then(|url| item_url(url, &path, kind));
+
+    ExternalItem {
+        crate_name,
+        version,
+        path,
+        kind,
+        exported_as,
+        docs_url,
+    }
+}
+
+/// Build the URL of an item's rustdoc page under a crate's docs root
+pub(super) fn item_url(root_url: &str, path: &[String], kind: ItemKind) -> Option<String> {
+    let root_url = root_url.trim_end_matches('/');
+    if kind == ItemKind::Module {
+        return Some(format!("{}/{}/index.html", root_url, path.join("/")));
+    }
+
+    let prefix = match kind {
+        ItemKind::Struct => "struct",
+        ItemKind::Enum => "enum",
+        ItemKind::Union => "union",
+        ItemKind::Trait => "trait",
+        ItemKind::TraitAlias => "traitalias",
+        ItemKind::Function => "fn",
+        ItemKind::TypeAlias => "type",
+        ItemKind::Constant => "constant",
+        ItemKind::Static => "static",
+        ItemKind::Macro => "macro",
+        ItemKind::ProcAttribute => "attr",
+        ItemKind::ProcDerive => "derive",
+        ItemKind::Primitive => "primitive",
+        ItemKind::Keyword => "keyword",
+        ItemKind::Attribute => "attribute",
+        _ => return None,
+    };
+    let (name, parents) = path.split_last()?;
+    Some(format!(
+        "{}/{}/{}.{}.html",
+        root_url,
+        parents.join("/"),
+        prefix,
+        name
+    ))
+}
@@ -0,0 +1,155 @@
+//! Small crates built in code, for unit tests that need a `Crate` to work on.
+
+use std::collections::HashMap;
+
+use super::model::{
+    Crate, Enum, ExternalCrate, Generics, Id, Item, ItemEnum, ItemKind, ItemSummary, Module, Trait,
+    Visibility,
+};
+
+/// Builds a crate item by item. Items get ids in the order they're added,
+/// starting after the root module.
+pub struct TestCrate {
+    name: String,
+    krate: Crate,
+}
+
+impl TestCrate {
+    /// A crate with nothing but an empty root module
+    pub fn new(name: &str) -> Self {
+        let root = Id(0);
+        let mut krate = Crate {
+            root,
+            crate_version: Some("1.0.0".to_string()),
+            includes_private: false,
+            index: HashMap::new(),
+            paths: HashMap::new(),
+            external_crates: HashMap::new(),
+            format_version: 57,
+            search_index: Default::default(),
+            item_paths: Default::default(),
+        };
+        krate.index.insert(
+            root,
+            item(
+                root,
+                name,
+                ItemEnum::Module(Module {
+                    is_crate: true,
+                    items: Vec::new(),
+                    is_stripped: false,
+                }),
+            ),
+        );
+        krate.paths.insert(
+            root,
+            ItemSummary {
+                crate_id: 0,
+                path: vec![name.to_string()],
+                kind: ItemKind::Module,
+            },
+        );
+        Self {
+            name: name.to_string(),
+            krate,
+        }
+    }
+
+    /// Add an item with a path of its own, like `module::Widget`, below the crate root
+    pub fn item(&mut self, path: &str, inner: ItemEnum) -> Id {
+        let mut full_path = vec![self.name.clone()];
+        full_path.extend(path.split("::").map(str::to_string));
+        let kind = inner.kind();
+        let id = self.child(full_path.last().unwrap(), inner);
+        self.krate.paths.insert(
+            id,
+            ItemSummary {
+                crate_id: 0,
+                path: full_path,
+                kind,
+            },
+        );
+        id
+    }
+
+    /// Add an item without a path of its own, like a trait method or an
+    /// enum variant, to be listed in its parent
+    pub fn child(&mut self, name: &str, inner: ItemEnum) -> Id {
+        let id = Id(self.krate.index.len() as u32);
+        self.krate.index.insert(id, item(id, name, inner));
+        id
+    }
+
+    /// Add an item of another crate, known only by its path
+    pub fn external(&mut self, crate_name: &str, path: &str, kind: ItemKind) -> Id {
+        let crate_id = self.krate.external_crates.len() as u32 + 1;
+        self.krate.external_crates.insert(
+            crate_id,
+            ExternalCrate {
+                name: crate_name.to_string(),
+                html_root_url: Some(format!("https://docs.rs/{}/latest/", crate_name)),
+            },
+        );
+        let id = Id(1000 + self.krate.paths.len() as u32);
+        self.krate.paths.insert(
+            id,
+            ItemSummary {
+                crate_id,
+                path: path.split("::").map(str::to_string).collect(),
+                kind,
+            },
+        );
+        id
+    }
+
+    /// The item with this id, to fill in its docs or links
+    pub fn get_mut(&mut self, id: Id) -> &mut Item {
+        self.krate.index.get_mut(&id).unwrap()
+    }
+
+    pub fn build(self) -> Crate {
+        self.krate
+    }
+}
+
+/// An enum with the given variants
+pub fn enum_of(variants: Vec<Id>) -> ItemEnum {
+    ItemEnum::Enum(Enum {
+        generics: Generics::default(),
+        has_stripped_variants: false,
+        variants,
+        impls: Vec::new(),
+    })
+}
+
+/// A trait with the given associated items
+pub fn trait_of(items: Vec<Id>) -> ItemEnum {
+    ItemEnum::Trait(Trait {
+        is_auto: false,
+        is_unsafe: false,
+        is_dyn_compatible: true,
+        items,
+        generics: Generics::default(),
+        bounds: Vec::new(),
+        implementations: Vec::new(),
+    })
+}
+
+/// An item whose details don't matter, only its kind
+pub fn opaque(kind: ItemKind) -> ItemEnum {
+    ItemEnum::Opaque(kind)
+}
+
+fn item(id: Id, name: &str, inner: ItemEnum) -> Item {
+    Item {
+        id,
+        crate_id: 0,
+        name: Some(name.to_string()),
+        visibility: Visibility::Public,
+        docs: None,
+        links: HashMap::new(),
+        attrs: Vec::new(),
+        deprecation: None,
+        inner,
+    }
+}
@@ -0,0 +1,664 @@
+use anyhow::Result;
+
+use crate::error::DocsError;
+
+use super::model::{Crate, Id, Item, ItemEnum, ItemKind, ItemSummary, Type, Variant, VariantKind};
+use super::reexport::Export;
+use super::resolve::{self, ItemQuery, Lookup, Resolution};
+use super::signature;
+use super::structured::{
+    self, AssocItemDoc, CandidateDoc, CrateDoc, DeprecationDoc, ExportDoc, FieldDoc, ImplDoc,
+    ImplKind, ItemDoc, ItemLookup, ReferenceDoc, StructKindDoc, VariantDoc,
+};
+
+/// Get the first line of documentation, truncated if too long
+pub(super) fn get_first_line(docs: &str) -> String {
+    let first_line = docs.lines().next().unwrap_or("").trim();
+    if first_line.len() > 100 {
+        let mut end = 97;
+        while !first_line.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}...", &first_line[..end])
+    } else {
+        first_line.to_string()
+    }
+}
+
+/// Format the first line of an item's documentation as a list entry suffix
+pub(super) fn doc_summary(item: &Item) -> String {
+    item.docs
+        .as_ref()
+        .map(|d| format!(": {}", get_first_line(d)))
+        .unwrap_or_default()
+}
+
+/// Format a module export as a list entry with its first line of documentation,
+/// noting where re-exported items come from
+pub(super) fn export_entry(export: &Export) -> String {
+    format_export(&structured::export_doc(export))
+}
+
+/// Format a summary as a list entry suffix
+fn summary_suffix(summary: Option<&String>) -> String {
+    summary
+        .map(|summary| format!(": {}", summary))
+        .unwrap_or_default()
+}
+
+fn format_export(export: &ExportDoc) -> String {
+    if let Some(source) = export.name.strip_suffix("::*") {
+        return format!("- `pub use {}::*`", source);
+    }
+    let origin = export
+        .reexport_of
+        .as_ref()
+        .map(|origin| format!(" (re-export of `{}`)", origin))
+        .unwrap_or_default();
+    format!(
+        "- **{}**{}{}",
+        export.name,
+        origin,
+        summary_suffix(export.summary.as_ref())
+    )
+}
+
+/// Whether an item is marked `#[non_exhaustive]`
+pub(super) fn is_non_exhaustive(item: &Item) -> bool {
+    item.attrs.iter().any(|attr| attr == "#[non_exhaustive]")
+}
+
+/// Resolve field ids into their items and types, skipping anything that
+/// isn't a field
+pub(super) fn resolve_fields<'a>(
+    krate: &'a Crate,
+    field_ids: impl IntoIterator<Item = &'a Id>,
+) -> impl Iterator<Item = (&'a Item, &'a Type)> {
+    field_ids
+        .into_iter()
+        .filter_map(|id| krate.index.get(id))
+        .filter_map(|field| match &field.inner {
+            ItemEnum::StructField(type_) => Some((field, type_)),
+            _ => None,
+        })
+}
+
+/// Format a variant's shape as it would be declared, e.g. `Move { x: i32, y: i32 } = 2`
+pub(super) fn format_variant_declaration(krate: &Crate, name: &str, variant: &Variant) -> String {
+    let mut declaration = name.to_string();
+
+    match &variant.kind {
+        VariantKind::Plain => {}
+        VariantKind::Tuple(fields) => {
+            // Stripped fields are shown as `_` to keep the positions right
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|id| {
+                    id.and_then(|id| match &krate.index.get(&id)?.inner {
+                        ItemEnum::StructField(type_) => Some(signature::format_type(type_)),
+                        _ => None,
+                    })
+                    .unwrap_or_else(|| "_".to_string())
+                })
+                .collect();
+            declaration.push_str(&format!("({})", fields.join(", ")));
+        }
+        VariantKind::Struct {
+            fields,
+            has_stripped_fields,
+        } => {
+            let mut fields: Vec<String> = resolve_fields(krate, fields)
+                .map(|(field, type_)| {
+                    format!(
+                        "{}: {}",
+                        field.name.as_deref().unwrap_or("_"),
+                        signature::format_type(type_)
+                    )
+                })
+                .collect();
+            if *has_stripped_fields {
+                fields.push("..".to_string());
+            }
+            declaration.push_str(&format!(" {{ {} }}", fields.join(", ")));
+        }
+    }
+
+    if let Some(discriminant) = &variant.discriminant {
+        declaration.push_str(&format!(" = {}", discriminant.expr));
+    }
+
+    declaration
+}
+
+/// Format the public fields of a struct or union as a section
+fn format_fields(fields: &[FieldDoc], has_hidden_fields: bool) -> Option<String> {
+    let mut entries: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            format!(
+                "- `{}: {}`{}",
+                field.name,
+                field.type_,
+                summary_suffix(field.summary.as_ref())
+            )
+        })
+        .collect();
+
+    if has_hidden_fields {
+        entries.push("\n_Some fields are private or hidden from the documentation._".to_string());
+    }
+
+    if entries.is_empty() {
+        None
+    } else {
+        Some(format!("\n## Fields\n{}", entries.join("\n")))
+    }
+}
+
+/// Format an enum variant as a list entry, followed by its documented struct fields
+fn variant_entry(variant: &VariantDoc) -> String {
+    let mut entry = format!(
+        "- `{}`{}{}",
+        variant.declaration,
+        if variant.non_exhaustive {
+            " (non-exhaustive)"
+        } else {
+            ""
+        },
+        summary_suffix(variant.summary.as_ref())
+    );
+
+    for field in &variant.fields {
+        if let Some(summary) = &field.summary {
+            entry.push_str(&format!("\n  - `{}`: {}", field.name, summary));
+        }
+    }
+
+    entry
+}
+
+/// Format the variants of an enum as a section
+fn format_variants(variants: &[VariantDoc], has_hidden_variants: bool) -> Option<String> {
+    let mut entries: Vec<String> = variants.iter().map(variant_entry).collect();
+
+    if has_hidden_variants {
+        entries.push("\n_Some variants are hidden from the documentation._".to_string());
+    }
+
+    if entries.is_empty() {
+        None
+    } else {
+        Some(format!("\n## Variants\n{}", entries.join("\n")))
+    }
+}
+
+fn assoc_item_entry(item: &AssocItemDoc) -> String {
+    format!(
+        "- `{}`{}",
+        item.declaration,
+        summary_suffix(item.summary.as_ref())
+    )
+}
+
+/// Format an impl block as a heading followed by its public associated items
+fn format_impl_block(impl_doc: &ImplDoc) -> String {
+    let mut lines = vec![format!("\n### `{}`", impl_doc.header)];
+    lines.extend(impl_doc.items.iter().map(assoc_item_entry));
+
+    if !impl_doc.provided_methods.is_empty() {
+        let provided: Vec<String> = impl_doc
+            .provided_methods
+            .iter()
+            .map(|name| format!("`{}`", name))
+            .collect();
+        lines.push(format!("- Provided: {}", provided.join(", ")));
+    }
+
+    lines.join("\n")
+}
+
+/// Format the impl blocks of a type: inherent impls first, then trait impls,
+/// with compiler-generated auto trait impls and blanket impls listed last as
+/// one-line headers
+fn format_impls(impls: &[ImplDoc]) -> Vec<String> {
+    let mut inherent = Vec::new();
+    let mut trait_impls = Vec::new();
+    let mut auto_trait_impls = Vec::new();
+    let mut blanket_impls = Vec::new();
+
+    for impl_doc in impls {
+        match impl_doc.kind {
+            ImplKind::Inherent => inherent.push(format_impl_block(impl_doc)),
+            ImplKind::Trait => trait_impls.push(format_impl_block(impl_doc)),
+            ImplKind::Auto => auto_trait_impls.push(format!("- `{}`", impl_doc.header)),
+            ImplKind::Blanket => blanket_impls.push(format!("- `{}`", impl_doc.header)),
+        }
+    }
+
+    let mut sections = Vec::new();
+    if !inherent.is_empty() {
+        sections.push(format!("\n## Implementations\n{}", inherent.join("\n")));
+    }
+    if !trait_impls.is_empty() {
+        sections.push(format!(
+            "\n## Trait Implementations\n{}",
+            trait_impls.join("\n")
+        ));
+    }
+    if !auto_trait_impls.is_empty() {
+        sections.push(format!(
+            "\n## Auto Trait Implementations\n{}",
+            auto_trait_impls.join("\n")
+        ));
+    }
+    if !blanket_impls.is_empty() {
+        sections.push(format!(
+            "\n## Blanket Implementations\n{}",
+            blanket_impls.join("\n")
+        ));
+    }
+    sections
+}
+
+/// Format a trait's associated items, splitting methods into required and provided ones
+fn format_trait_items(items: &[AssocItemDoc]) -> Vec<String> {
+    let mut assoc_items = Vec::new();
+    let mut required = Vec::new();
+    let mut provided = Vec::new();
+
+    for item in items {
+        let entry = assoc_item_entry(item);
+        if item.kind != ItemKind::Function.name() {
+            assoc_items.push(entry);
+        } else if item.provided {
+            provided.push(entry);
+        } else {
+            required.push(entry);
+        }
+    }
+
+    let mut sections = Vec::new();
+    if !assoc_items.is_empty() {
+        sections.push(format!("\n## Associated Items\n{}", assoc_items.join("\n")));
+    }
+    if !required.is_empty() {
+        sections.push(format!("\n## Required Methods\n{}", required.join("\n")));
+    }
+    if !provided.is_empty() {
+        sections.push(format!("\n## Provided Methods\n{}", provided.join("\n")));
+    }
+    sections
+}
+
+fn format_deprecation(deprecation: &DeprecationDoc) -> String {
+    let mut notice = "\n⚠️ **Deprecated**".to_string();
+    if let Some(since) = &deprecation.since {
+        notice.push_str(&format!(" since {}", since));
+    }
+    if let Some(note) = &deprecation.note {
+        notice.push_str(&format!(": {}", note));
+    }
+    notice
+}
+
+/// Format the items linked from documentation as a list section
+fn format_references(references: &[ReferenceDoc]) -> Option<String> {
+    if references.is_empty() {
+        return None;
+    }
+
+    let entries: Vec<String> = references
+        .iter()
+        .map(|reference| {
+            format!(
+                "- **{}** `{}`{}",
+                reference.kind,
+                reference.path,
+                summary_suffix(reference.docs_url.as_ref())
+            )
+        })
+        .collect();
+
+    Some(format!("\n## Referenced Items\n{}", entries.join("\n")))
+}
+
+/// Format a single item. The name, kind, path and signature come before any
+/// section, so they are kept when a response is cut down to size.
+fn format_item(doc: &ItemDoc) -> String {
+    let mut sections = vec![format!("# {}", doc.name)];
+
+    sections.push(format!("\n**Type:** {}", doc.kind));
+
+    // Full path, when it says more than the name
+    if doc.path.contains("::") {
+        sections.push(format!("**Path:** `{}`", doc.path));
+    }
+    if let Some(origin) = &doc.reexport_of {
+        sections.push(format!("**Re-export of:** `{}`", origin));
+    }
+    if let Some(visibility) = &doc.visibility {
+        sections.push(format!("**Visibility:** `{}`", visibility));
+    }
+    if let Some(url) = &doc.docs_url {
+        sections.push(format!("**Docs:** {}", url));
+    }
+
+    if let Some(signature) = &doc.signature {
+        sections.push(format!("\n```rust\n{}\n```", signature));
+    }
+
+    if let Some(deprecation) = &doc.deprecation {
+        sections.push(format_deprecation(deprecation));
+    }
+
+    if let Some(docs) = &doc.docs {
+        sections.push(format!("\n## Documentation\n{}", docs));
+    }
+
+    if let Some(struct_kind) = doc.struct_kind {
+        sections.push(format!(
+            "\n**Struct Type:** {}",
+            match struct_kind {
+                StructKindDoc::Plain => "plain",
+                StructKindDoc::Tuple => "tuple",
+                StructKindDoc::Unit => "unit",
+            }
+        ));
+    }
+
+    if doc.non_exhaustive {
+        if doc.kind == ItemKind::Struct.name() {
+            sections.push(
+                "\n**Non-exhaustive:** can't be built with a struct literal outside its crate, \
+                 and more fields may be added"
+                    .to_string(),
+            );
+        } else if doc.kind == ItemKind::Enum.name() {
+            sections.push(
+                "\n**Non-exhaustive:** matches outside its crate need a wildcard arm, \
+                 and more variants may be added"
+                    .to_string(),
+            );
+        }
+    }
+
+    if !doc.qualifiers.is_empty() {
+        sections.push(format!("\n**Attributes:** {}", doc.qualifiers.join(", ")));
+    }
+
+    sections.extend(format_fields(&doc.fields, doc.has_hidden_fields));
+    sections.extend(format_variants(&doc.variants, doc.has_hidden_variants));
+    sections.extend(format_trait_items(&doc.trait_items));
+    if let Some(count) = doc.implementor_count.filter(|&count| count > 0) {
+        sections.push(format!("\n**Implementors:** {} impl block(s)", count));
+    }
+    sections.extend(format_impls(&doc.impls));
+    sections.extend(format_references(&doc.references));
+
+    sections.join("\n")
+}
+
+/// Format the crate root: its docs and public items grouped by kind
+pub fn format_crate_doc(doc: &CrateDoc) -> String {
+    let mut header = format!("# Crate: {}", doc.name);
+    if let Some(version) = &doc.version {
+        header.push_str(&format!(" v{}", version));
+    }
+    let mut sections = vec![header];
+
+    if let Some(docs) = &doc.docs {
+        sections.push(format!("\n## Documentation\n{}", docs));
+    }
+
+    for (kind, title) in [
+        (ItemKind::Module, "Modules"),
+        (ItemKind::Struct, "Structs"),
+        (ItemKind::Enum, "Enums"),
+        (ItemKind::Trait, "Traits"),
+        (ItemKind::Function, "Functions"),
+    ] {
+        let entries: Vec<String> = doc
+            .items
+            .iter()
+            .filter(|export| export.kind == kind.name())
+            .map(format_export)
+            .collect();
+        if !entries.is_empty() {
+            sections.push(format!("\n## {}\n{}", title, entries.join("\n")));
+        }
+    }
+
+    sections.extend(format_references(&doc.references));
+
+    sections.join("\n")
+}
+
+/// Format an associated function, constant or type as a single-line declaration
+pub(super) fn assoc_item_declaration(item: &Item) -> Option<String> {
+    let name = item.name.as_ref()?;
+    let declaration = match &item.inner {
+        ItemEnum::Function(func) => {
+            signature::format_function_signature_inline(name, &item.visibility, func)
+        }
+        ItemEnum::AssocConst { type_, value } => {
+            let mut declaration = format!(
+                "{}const {}: {}",
+                signature::format_visibility(&item.visibility),
+                name,
+                signature::format_type(type_)
+            );
+            if let Some(value) = value {
+                declaration.push_str(&format!(" = {}", value));
+            }
+            declaration
+        }
+        ItemEnum::AssocType {
+            generics,
+            bounds,
+            type_,
+        } => {
+            let mut declaration = format!(
+                "type {}{}",
+                name,
+                signature::format_generic_params(&generics.params)
+            );
+            if !bounds.is_empty() {
+                declaration.push_str(&format!(": {}", signature::format_bounds(bounds)));
+            }
+            if let Some(type_) = type_ {
+                declaration.push_str(&format!(" = {}", signature::format_type(type_)));
+            }
+            declaration.push_str(&signature::format_where_clause_inline(
+                &generics.where_predicates,
+            ));
+            declaration
+        }
+        _ => return None,
+    };
+    Some(declaration)
+}
+
+/// Maximum number of candidates listed for an ambiguous item path
+const MAX_CANDIDATES: usize = 20;
+
+/// Format a looked up item from its description, or list the candidates if
+/// its path is ambiguous
+pub fn format_lookup(item_path: &str, lookup: &Lookup, result: &ItemLookup) -> String {
+    if !result.candidates.is_empty() {
+        return ambiguous_candidates(item_path, &result.candidates);
+    }
+    let Some(item) = &result.item else {
+        return String::new();
+    };
+
+    let mut text = format_item(item);
+    if let Lookup::External(external) = lookup {
+        text.push_str(&format!(
+            "\n\nThis item is defined in the `{}` crate, look it up there for its documentation.",
+            external.crate_name
+        ));
+    }
+    text
+}
+
+/// List the candidates for an ambiguous item path
+pub(super) fn ambiguous_candidates(item_path: &str, candidates: &[CandidateDoc]) -> String {
+    let mut lines = vec![format!(
+        "Multiple items match '{}'. Use a full path or a kind prefix like `struct.Name` \
+         or `fn.name` to pick one:\n",
+        item_path
+    )];
+    for candidate in candidates.iter().take(MAX_CANDIDATES) {
+        lines.push(format!(
+            "- **{}** `{}`{}",
+            candidate.kind,
+            candidate.path,
+            summary_suffix(candidate.summary.as_ref())
+        ));
+    }
+    if candidates.len() > MAX_CANDIDATES {
+        lines.push(format!(
+            "- ...and {} more",
+            candidates.len() - MAX_CANDIDATES
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// Traits of other crates matching a query, which are only known by path.
+/// A trait whose full path is the query wins over the rest.
+fn external_traits<'a>(krate: &'a Crate, query: &ItemQuery) -> Vec<(Id, &'a ItemSummary)> {
+    let mut matches: Vec<(Id, &ItemSummary)> = krate
+        .paths
+        .iter()
+        .filter(|(id, summary)| {
+            summary.crate_id != 0
+                && !krate.index.contains_key(id)
+                && query.matches(&summary.path, summary.kind)
+        })
+        .map(|(id, summary)| (*id, summary))
+        .collect();
+    if let Some(exact) = matches
+        .iter()
+        .position(|(_, summary)| summary.path == query.segments)
+    {
+        return vec![matches.swap_remove(exact)];
+    }
+    matches.sort_by(|(_, a), (_, b)| a.path.cmp(&b.path));
+    matches
+}
+
+/// List every impl of a trait in a crate, along with the trait's required and
+/// provided methods
+pub fn find_implementors(krate: &Crate, trait_path: &str) -> Result<String> {
+    let trait_query = format!("trait.{}", trait_path.trim().trim_start_matches("trait."));
+    let query = ItemQuery::parse(&trait_query)?;
+
+    let (trait_id, path, crate_id) = match resolve::resolve_item(krate, &trait_query) {
+        Ok(Resolution::Found(candidate)) => {
+            (candidate.item.id, candidate.path, candidate.item.crate_id)
+        }
+        Ok(Resolution::Ambiguous(candidates)) => {
+            return Ok(ambiguous_candidates(
+                trait_path,
+                &structured::candidate_docs(&candidates),
+            ))
+        }
+        // Traits of other crates aren't in the index
+        Err(_) => match external_traits(krate, &query).as_slice() {
+            [] => {
+                return Err(DocsError::ItemNotFound {
+                    kind: "Trait",
+                    path: trait_path.to_string(),
+                }
+                .into())
+            }
+            [(id, summary)] => (*id, summary.path.clone(), summary.crate_id),
+            several => {
+                let mut lines = vec![format!(
+                    "Multiple traits match '{}'. Use a full path to pick one:\n",
+                    trait_path
+                )];
+                lines.extend(
+                    several
+                        .iter()
+                        .map(|(_, summary)| format!("- `{}`", summary.path.join("::"))),
+                );
+                return Ok(lines.join("\n"));
+            }
+        },
+    };
+
+    let trait_data = match krate.index.get(&trait_id).map(|item| &item.inner) {
+        Some(ItemEnum::Trait(trait_data)) => Some(trait_data),
+        _ => None,
+    };
+
+    let mut sections = vec![format!("# Implementors of {}", path.join("::"))];
+
+    let impl_ids: Vec<Id> = match trait_data {
+        Some(trait_data) => {
+            sections.extend(format_trait_items(&structured::trait_item_docs(
+                krate, trait_data,
+            )));
+            trait_data.implementations.clone()
+        }
+        // Traits from other crates aren't in the index, so look for impls of them instead
+        None => {
+            if let Some(external) = krate.external_crates.get(&crate_id) {
+                sections.push(format!(
+                    "\nDefined in `{}`; look up the trait there for its methods.",
+                    external.name
+                ));
+            }
+            let mut impl_ids: Vec<Id> = krate
+                .index
+                .values()
+                .filter(|item| match &item.inner {
+                    ItemEnum::Impl(impl_data) => impl_data
+                        .trait_
+                        .as_ref()
+                        .is_some_and(|trait_| trait_.id == trait_id),
+                    _ => false,
+                })
+                .map(|item| item.id)
+                .collect();
+            impl_ids.sort();
+            impl_ids
+        }
+    };
+
+    let implementors: Vec<String> = impl_ids
+        .iter()
+        .filter_map(|id| match &krate.index.get(id)?.inner {
+            ItemEnum::Impl(impl_data) => {
+                let flag = if impl_data.is_synthetic {
+                    " (auto trait)"
+                } else if impl_data.blanket_impl.is_some() {
+                    " (blanket)"
+                } else {
+                    ""
+                };
+                Some(format!(
+                    "- `{}`{}",
+                    signature::format_impl_header(impl_data),
+                    flag
+                ))
+            }
+            _ => None,
+        })
+        .collect();
+
+    if implementors.is_empty() {
+        sections.push("\n## Implementors\nNo implementations found in this crate.".to_string());
+    } else {
+        sections.push(format!(
+            "\n## Implementors ({})\n{}",
+            implementors.len(),
+            implementors.join("\n")
+        ));
+    }
+
+    Ok(sections.join("\n"))
+}
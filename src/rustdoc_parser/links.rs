@@ -0,0 +1,437 @@
+//! Rewriting intra-doc links in documentation to fully qualified paths.
+//!
+//! Rustdoc resolves intra-doc links at build time and records their targets
+//! in `Item::links`, keyed by the link destination as written: the text of
+//! shortcut links like ``[`Sender`]``, or the destination of `[text](path)`
+//! and of reference definitions. Rewriting those destinations to full paths
+//! lets them be passed straight back to item lookup.
+
+use std::collections::HashMap;
+
+use super::external;
+use super::model::{Crate, Id, Item, ItemKind};
+use super::resolve;
+
+/// An item linked from documentation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkTarget {
+    pub path: Vec<String>,
+    pub kind: ItemKind,
+    /// Link to the item's page on docs.rs, or the standard library docs
+    pub docs_url: Option<String>,
+}
+
+/// Rendered documentation and the items it links to, in order of first mention
+#[derive(Debug, Clone, Default)]
+pub struct RenderedDocs {
+    pub text: String,
+    pub references: Vec<LinkTarget>,
+}
+
+/// Render an item's docs with its intra-doc links pointing at full item paths
+pub fn render_docs(krate: &Crate, item: &Item) -> Option<RenderedDocs> {
+    let docs = item.docs.as_ref()?;
+    if item.links.is_empty() {
+        return Some(RenderedDocs {
+            text: docs.clone(),
+            references: Vec::new(),
+        });
+    }
+
+    let mut resolver = LinkResolver::new(krate);
+    let mut references: Vec<LinkTarget> = Vec::new();
+    let mut target_path = |destination: &str| -> Option<String> {
+        let id = item.links.get(destination)?;
+        let target = resolver.resolve(id)?;
+        let path = target.path.join("::");
+        if !references.contains(&target) {
+            references.push(target);
+        }
+        Some(path)
+    };
+
+    let mut lines = Vec::new();
+    let mut fence: Option<&str> = None;
+    for line in docs.lines() {
+        let trimmed = line.trim_start();
+
+        // Links aren't resolved inside code blocks
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            lines.push(line.to_string());
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker))
+        {
+            fence = Some(marker);
+            lines.push(line.to_string());
+            continue;
+        }
+
+        match rewrite_definition(line, &mut target_path) {
+            Some(rewritten) => lines.push(rewritten),
+            None => lines.push(rewrite_links(line, &mut target_path)),
+        }
+    }
+
+    Some(RenderedDocs {
+        text: lines.join("\n"),
+        references,
+    })
+}
+
+/// Rewrite a reference definition like `[label]: crate::Widget`, whose
+/// destination is what rustdoc resolved
+fn rewrite_definition(
+    line: &str,
+    target_path: &mut impl FnMut(&str) -> Option<String>,
+) -> Option<String> {
+    let trimmed = line.trim_start();
+    let rest = trimmed.strip_prefix('[')?;
+    let (label, destination) = rest.split_once("]:")?;
+    let path = target_path(destination.trim())?;
+    let indent = &line[..line.len() - trimmed.len()];
+    Some(format!("{}[{}]: {}", indent, label, path))
+}
+
+/// Rewrite the inline and shortcut links on a line, leaving code spans,
+/// reference links and links rustdoc didn't resolve untouched
+fn rewrite_links(line: &str, target_path: &mut impl FnMut(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(pos) = rest.find(['[', '`']) {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+
+        if rest.starts_with('`') {
+            let span = code_span_len(rest);
+            out.push_str(&rest[..span]);
+            rest = &rest[span..];
+            continue;
+        }
+
+        let Some(text_len) = bracket_len(rest) else {
+            out.push('[');
+            rest = &rest[1..];
+            continue;
+        };
+        let text = &rest[1..text_len - 1];
+        let after = &rest[text_len..];
+
+        // `[text](destination)`
+        if let Some(inner) = after.strip_prefix('(') {
+            if let Some(end) = inner.find(')') {
+                // Drop any title, as in `[text](path "title")`
+                let destination = inner[..end].split_whitespace().next().unwrap_or_default();
+                let destination = destination
+                    .strip_prefix('<')
+                    .and_then(|d| d.strip_suffix('>'))
+                    .unwrap_or(destination);
+                match target_path(destination) {
+                    Some(path) => out.push_str(&format!("[{}]({})", text, path)),
+                    None => out.push_str(&rest[..text_len + 1 + end + 1]),
+                }
+                rest = &inner[end + 1..];
+                continue;
+            }
+        }
+
+        // `[text][label]` and `[text][]` go through their definitions
+        if after.starts_with('[') {
+            let label_len = bracket_len(after).unwrap_or(1);
+            out.push_str(&rest[..text_len + label_len]);
+            rest = &after[label_len..];
+            continue;
+        }
+
+        // Shortcut `[text]`, where the text is the destination
+        match target_path(text) {
+            Some(path) => out.push_str(&format!("[{}]({})", text, path)),
+            None => out.push_str(&rest[..text_len]),
+        }
+        rest = after;
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Length of the code span at the start of `text`, up to the matching run of
+/// backticks, or of the backticks alone if the span isn't closed
+fn code_span_len(text: &str) -> usize {
+    let ticks = text.len() - text.trim_start_matches('`').len();
+    let fence = &text[..ticks];
+    match text[ticks..].find(fence) {
+        Some(end) => ticks + end + ticks,
+        None => ticks,
+    }
+}
+
+/// Length of the bracketed text at the start of `text`, including both
+/// brackets, allowing nested brackets and code spans inside
+fn bracket_len(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut pos = 0;
+    while pos < text.len() {
+        match text.as_bytes()[pos] {
+            b'`' => {
+                pos += code_span_len(&text[pos..]);
+                continue;
+            }
+            b'[' => depth += 1,
+            b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(pos + 1);
+                }
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+    None
+}
+
+/// Maps link target ids to paths, falling back to a full scan of the crate
+/// for items without a `paths` entry, like methods and fields
+struct LinkResolver<'a> {
+    krate: &'a Crate,
+    child_paths: Option<HashMap<Id, Vec<String>>>,
+}
+
+impl<'a> LinkResolver<'a> {
+    fn new(krate: &'a Crate) -> Self {
+        Self {
+            krate,
+            child_paths: None,
+        }
+    }
+
+    fn resolve(&mut self, id: &Id) -> Option<LinkTarget> {
+        let krate = self.krate;
+        if let Some(summary) = krate.paths.get(id) {
+            return Some(LinkTarget {
+                path: summary.path.clone(),
+                kind: summary.kind,
+                docs_url: docs_url(krate, summary.crate_id, &summary.path, summary.kind),
+            });
+        }
+
+        let item = krate.index.get(id)?;
+        let child_paths = self.child_paths.get_or_insert_with(|| {
+            let mut paths = HashMap::new();
+            for (item, path) in resolve::item_paths(krate) {
+                paths.entry(item.id).or_insert_with(|| path.to_vec());
+            }
+            paths
+        });
+        Some(LinkTarget {
+            path: child_paths.get(id)?.clone(),
+            kind: item.inner.kind(),
+            docs_url: None,
+        })
+    }
+}
+
+/// URL of an item's page, on docs.rs for this crate and from the docs root
+/// rustdoc recorded for dependencies
+pub(super) fn docs_url(
+    krate: &Crate,
+    crate_id: u32,
+    path: &[String],
+    kind: ItemKind,
+) -> Option<String> {
+    let root_url = if crate_id == 0 {
+        let name = krate.index.get(&krate.root)?.name.as_ref()?;
+        let version = krate.crate_version.as_deref().unwrap_or("latest");
+        format!("https://docs.rs/{}/{}/", name, version)
+    } else {
+        krate
+            .external_crates
+            .get(&crate_id)?
+            .html_root_url
+            .clone()?
+    };
+    external::item_url(&root_url, path, kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rustdoc_parser::fixture::{opaque, trait_of, TestCrate};
+
+    /// Rewrite with a fixed set of resolved destinations
+    fn rewrite(line: &str) -> String {
+        let targets = HashMap::from([
+            ("`Sender`", "chan::Sender"),
+            ("Sender", "chan::Sender"),
+            ("Receiver", "chan::Receiver"),
+            ("`Vec<[u8]>`", "alloc::vec::Vec"),
+        ]);
+        rewrite_links(line, &mut |destination| {
+            targets.get(destination).map(|path| path.to_string())
+        })
+    }
+
+    #[test]
+    fn rewrites_shortcut_links() {
+        assert_eq!(
+            rewrite("See [`Sender`] and [Receiver]."),
+            "See [`Sender`](chan::Sender) and [Receiver](chan::Receiver)."
+        );
+    }
+
+    #[test]
+    fn rewrites_inline_links() {
+        assert_eq!(
+            rewrite("Use [the sender](Sender) here"),
+            "Use [the sender](chan::Sender) here"
+        );
+        assert_eq!(
+            rewrite("[the sender](<Sender> \"Sending half\")"),
+            "[the sender](chan::Sender)"
+        );
+    }
+
+    #[test]
+    fn leaves_reference_links_to_their_definitions() {
+        assert_eq!(rewrite("[the sender][Sender]"), "[the sender][Sender]");
+        assert_eq!(rewrite("[Sender][]"), "[Sender][]");
+
+        let mut target_path =
+            |destination: &str| (destination == "Sender").then(|| "chan::Sender".to_string());
+        assert_eq!(
+            rewrite_definition("  [sender]: Sender", &mut target_path).as_deref(),
+            Some("  [sender]: chan::Sender")
+        );
+        assert_eq!(rewrite_definition("[other]: Other", &mut target_path), None);
+        assert_eq!(
+            rewrite_definition("[Sender] is here", &mut target_path),
+            None
+        );
+    }
+
+    #[test]
+    fn leaves_code_spans_unchanged() {
+        assert_eq!(rewrite("`[Sender]` stays"), "`[Sender]` stays");
+        assert_eq!(rewrite("``a ` [Sender]`` stays"), "``a ` [Sender]`` stays");
+        // A backtick without a closing one is just a backtick
+        assert_eq!(rewrite("` [Sender]"), "` [Sender](chan::Sender)");
+    }
+
+    #[test]
+    fn handles_nested_brackets() {
+        assert_eq!(
+            rewrite("[a [`Sender`] b](Sender)"),
+            "[a [`Sender`] b](chan::Sender)"
+        );
+        assert_eq!(rewrite("[`Vec<[u8]>`]"), "[`Vec<[u8]>`](alloc::vec::Vec)");
+    }
+
+    #[test]
+    fn leaves_unresolved_links_unchanged() {
+        for line in [
+            "[Unknown] and [text](Unknown)",
+            "[unclosed and ] stray",
+            "[text](unclosed",
+            "[]",
+        ] {
+            assert_eq!(rewrite(line), line);
+        }
+    }
+
+    #[test]
+    fn measures_code_spans_and_brackets() {
+        assert_eq!(code_span_len("`a` b"), 3);
+        assert_eq!(code_span_len("``a ` b`` c"), 9);
+        assert_eq!(code_span_len("``a"), 2);
+
+        assert_eq!(bracket_len("[a] b"), Some(3));
+        assert_eq!(bracket_len("[a [b] c] d"), Some(9));
+        assert_eq!(bracket_len("[`]` c] d"), Some(7));
+        assert_eq!(bracket_len("[a [b]"), None);
+    }
+
+    #[test]
+    fn renders_docs_with_links_outside_code_blocks() {
+        let mut krate = TestCrate::new("chan");
+        let sender = krate.item("Sender", opaque(ItemKind::Struct));
+        let send = krate.child("send", opaque(ItemKind::Function));
+        krate.item("Send", trait_of(vec![send]));
+        let string = krate.external("alloc", "alloc::string::String", ItemKind::Struct);
+        let receiver = krate.item("Receiver", opaque(ItemKind::Struct));
+
+        let docs = [
+            "Pairs with [`Sender`], see [`Sender`] and [send](Send::send).",
+            "```",
+            "let x = [`Sender`];",
+            "```",
+            "Takes a [String] and [what][w].",
+            "",
+            "[w]: Unknown",
+        ];
+        let item = krate.get_mut(receiver);
+        item.docs = Some(docs.join("\n"));
+        item.links = HashMap::from([
+            ("`Sender`".to_string(), sender),
+            ("Send::send".to_string(), send),
+            ("String".to_string(), string),
+        ]);
+        let krate = krate.build();
+
+        let rendered = render_docs(&krate, &krate.index[&receiver]).unwrap();
+        assert_eq!(
+            rendered.text.lines().collect::<Vec<_>>(),
+            [
+                "Pairs with [`Sender`](chan::Sender), see [`Sender`](chan::Sender) and \
+                 [send](chan::Send::send).",
+                "```",
+                "let x = [`Sender`];",
+                "```",
+                "Takes a [String](alloc::string::String) and [what][w].",
+                "",
+                "[w]: Unknown",
+            ]
+        );
+        assert_eq!(
+            rendered.references,
+            [
+                LinkTarget {
+                    path: vec!["chan".to_string(), "Sender".to_string()],
+                    kind: ItemKind::Struct,
+                    docs_url: Some(
+                        "https://docs.rs/chan/1.0.0/chan/struct.Sender.html".to_string()
+                    ),
+                },
+                LinkTarget {
+                    path: vec!["chan".to_string(), "Send".to_string(), "send".to_string()],
+                    kind: ItemKind::Function,
+                    docs_url: None,
+                },
+                LinkTarget {
+                    path: vec![
+                        "alloc".to_string(),
+                        "string".to_string(),
+                        "String".to_string()
+                    ],
+                    kind: ItemKind::Struct,
+                    docs_url: Some(
+                        "https://docs.rs/alloc/latest/alloc/string/struct.String.html".to_string()
+                    ),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn resolver_skips_ids_it_cannot_find() {
+        let krate = TestCrate::new("chan").build();
+        let mut resolver = LinkResolver::new(&krate);
+        assert_eq!(resolver.resolve(&Id(42)), None);
+    }
+}
@@ -0,0 +1,202 @@
+//! Listing the contents of a module, optionally recursing into submodules,
+//! with cursor-based pagination for very large modules.
+
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Result};
+
+use crate::error::DocsError;
+
+use super::format::export_entry;
+use super::model::{Crate, Id, ItemKind};
+use super::reexport::{self, Export};
+use super::resolve::{self, Resolution};
+
+/// Section headings in the order they're listed, following rustdoc's module pages
+const SECTIONS: &[(ItemKind, &str)] = &[
+    (ItemKind::Use, "Re-exports"),
+    (ItemKind::Module, "Modules"),
+    (ItemKind::Macro, "Macros"),
+    (ItemKind::ProcAttribute, "Attribute Macros"),
+    (ItemKind::ProcDerive, "Derive Macros"),
+    (ItemKind::Struct, "Structs"),
+    (ItemKind::Enum, "Enums"),
+    (ItemKind::Variant, "Variants"),
+    (ItemKind::Union, "Unions"),
+    (ItemKind::Trait, "Traits"),
+    (ItemKind::TraitAlias, "Trait Aliases"),
+    (ItemKind::Function, "Functions"),
+    (ItemKind::TypeAlias, "Type Aliases"),
+    (ItemKind::Constant, "Constants"),
+    (ItemKind::Static, "Statics"),
+    (ItemKind::ExternType, "Foreign Types"),
+    (ItemKind::ExternCrate, "Extern Crates"),
+    (ItemKind::Primitive, "Primitive Types"),
+    (ItemKind::Keyword, "Keywords"),
+    (ItemKind::Attribute, "Attributes"),
+];
+
+/// Position of a kind in `SECTIONS`, or after all of them
+fn section_index(kind: ItemKind) -> usize {
+    SECTIONS
+        .iter()
+        .position(|(section_kind, _)| *section_kind == kind)
+        .unwrap_or(SECTIONS.len())
+}
+
+fn section_heading(kind: ItemKind) -> &'static str {
+    SECTIONS
+        .get(section_index(kind))
+        .map(|(_, heading)| *heading)
+        .unwrap_or("Other Items")
+}
+
+/// One listed export, along with the module it's listed under
+struct Row<'a> {
+    module: String,
+    export: Export<'a>,
+}
+
+/// Collect the exports of a module, then those of its submodules down to `depth`
+fn collect_rows<'a>(
+    krate: &'a Crate,
+    module_id: Id,
+    module_path: &str,
+    depth: usize,
+    visited: &mut HashSet<Id>,
+    rows: &mut Vec<Row<'a>>,
+) {
+    if !visited.insert(module_id) {
+        return;
+    }
+
+    let mut exports = reexport::module_exports(krate, &module_id);
+    exports.sort_by(|a, b| {
+        section_index(a.kind)
+            .cmp(&section_index(b.kind))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let submodules: Vec<(Id, String)> = exports
+        .iter()
+        .filter(|export| export.kind == ItemKind::Module)
+        .filter_map(|export| Some((export.item?.id, export.name.clone())))
+        .collect();
+
+    rows.extend(exports.into_iter().map(|export| Row {
+        module: module_path.to_string(),
+        export,
+    }));
+
+    if depth > 1 {
+        for (id, name) in submodules {
+            let child_path = format!("{}::{}", module_path, name);
+            collect_rows(krate, id, &child_path, depth - 1, visited, rows);
+        }
+    }
+}
+
+/// List the public items of a module grouped by kind.
+///
+/// `module_path` may be empty for the crate root. With `depth` above 1,
+/// submodules are listed too. Results are paginated `limit` rows at a time;
+/// `cursor` is the value returned with the previous page.
+pub fn list_module(
+    krate: &Crate,
+    module_path: Option<&str>,
+    depth: usize,
+    cursor: Option<&str>,
+    limit: usize,
+) -> Result<String> {
+    let (module_id, path) = match module_path.map(str::trim).filter(|path| !path.is_empty()) {
+        None => {
+            let root = krate
+                .index
+                .get(&krate.root)
+                .ok_or_else(|| anyhow!("Root module not found in index"))?;
+            (root.id, root.name.clone().unwrap_or_default())
+        }
+        Some(module_path) => {
+            let resolution = resolve::resolve_item(krate, &format!("mod.{}", module_path))
+                .map_err(|_| DocsError::ItemNotFound {
+                    kind: "Module",
+                    path: module_path.to_string(),
+                })?;
+            match resolution {
+                Resolution::Found(candidate) => (candidate.item.id, candidate.path.join("::")),
+                Resolution::Ambiguous(candidates) => {
+                    let paths: Vec<String> = candidates
+                        .iter()
+                        .map(|candidate| format!("- `{}`", candidate.path.join("::")))
+                        .collect();
+                    return Err(DocsError::InvalidArgument(format!(
+                        "Module '{}' is ambiguous, use one of:\n{}",
+                        module_path,
+                        paths.join("\n")
+                    ))
+                    .into());
+                }
+            }
+        }
+    };
+
+    let start = match cursor {
+        Some(cursor) => cursor
+            .parse::<usize>()
+            .map_err(|_| DocsError::InvalidArgument(format!("Invalid cursor '{}'", cursor)))?,
+        None => 0,
+    };
+
+    let mut rows = Vec::new();
+    collect_rows(
+        krate,
+        module_id,
+        &path,
+        depth.max(1),
+        &mut HashSet::new(),
+        &mut rows,
+    );
+
+    if rows.is_empty() {
+        return Ok(format!("# Module {}\n\nNo public items.", path));
+    }
+    if start >= rows.len() {
+        return Err(DocsError::InvalidArgument(format!(
+            "Cursor {} is past the end of the listing ({} items)",
+            start,
+            rows.len()
+        ))
+        .into());
+    }
+
+    let end = (start + limit.max(1)).min(rows.len());
+    let mut sections = vec![format!(
+        "# Module {}\n\nShowing items {}-{} of {}",
+        path,
+        start + 1,
+        end,
+        rows.len()
+    )];
+
+    let mut current: Option<(&str, ItemKind)> = None;
+    for row in &rows[start..end] {
+        if current.map(|(module, _)| module) != Some(row.module.as_str()) && row.module != path {
+            sections.push(format!("\n## {}", row.module));
+        }
+        if current != Some((row.module.as_str(), row.export.kind)) {
+            let level = if row.module == path { "##" } else { "###" };
+            sections.push(format!("\n{} {}", level, section_heading(row.export.kind)));
+        }
+        current = Some((row.module.as_str(), row.export.kind));
+        sections.push(export_entry(&row.export));
+    }
+
+    if end < rows.len() {
+        sections.push(format!(
+            "\nMore items available, pass cursor `{}` to continue.",
+            end
+        ));
+    }
+
+    Ok(sections.join("\n"))
+}
@@ -1,5 +1,28 @@
-use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
 
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+
+use crate::error::DocsError;
+
+mod changes;
+mod complete;
+mod convert;
+mod examples;
+mod external;
+#[cfg(test)]
+mod fixture;
+mod format;
+mod links;
+mod listing;
+mod model;
+mod reexport;
+mod resolve;
+mod search;
+mod signature;
+mod structured;
+mod type_search;
+mod untyped;
 mod v46;
 mod v48;
 mod v49;
@@ -7,90 +30,99 @@ mod v50;
 mod v51;
 mod v52;
 mod v53;
+mod v54;
+mod v55;
+mod v56;
+mod v57;
 mod version;
 
-use self::version::get_format_version;
+pub use self::changes::compare_versions;
+pub use self::complete::complete_item_path;
+pub use self::examples::format_examples;
+pub use self::external::ExternalItem;
+pub use self::format::{find_implementors, format_crate_doc, format_lookup};
+pub use self::listing::list_module;
+pub use self::model::Crate;
+pub use self::resolve::{lookup_item, Lookup};
+pub use self::search::search_items;
+pub use self::structured::{
+    crate_doc, describe_examples, describe_lookup, CrateDoc, CrateRef, ItemLookup,
+};
+pub use self::type_search::search_by_type;
+pub use self::version::{get_format_version, get_version_info};
 
-/// Parse the main crate information based on the rustdoc format version
-pub fn parse_crate_info(json_str: &str) -> Result<String> {
-    // First, extract just the format version without full deserialization
-    let format_version = get_format_version(json_str)?;
+/// Newest format version we have typed support for, requested from docs.rs
+/// when a crate's default JSON is in a format we can't read
+pub const LATEST_FORMAT_VERSION: u32 = 57;
 
-    // Dispatch to the appropriate parser based on version
-    match format_version {
-        53 => {
-            let rustdoc: rustdoc_types_v53::Crate = serde_json::from_str(json_str)?;
-            v53::parse_crate_info(&rustdoc)
-        }
-        52 => {
-            let rustdoc: rustdoc_types_v52::Crate = serde_json::from_str(json_str)?;
-            v52::parse_crate_info(&rustdoc)
-        }
-        51 => {
-            let rustdoc: rustdoc_types_v51::Crate = serde_json::from_str(json_str)?;
-            v51::parse_crate_info(&rustdoc)
-        }
-        50 => {
-            let rustdoc: rustdoc_types_v50::Crate = serde_json::from_str(json_str)?;
-            v50::parse_crate_info(&rustdoc)
-        }
-        49 => {
-            let rustdoc: rustdoc_types_v49::Crate = serde_json::from_str(json_str)?;
-            v49::parse_crate_info(&rustdoc)
-        }
-        48 => {
-            let rustdoc: rustdoc_types_v48::Crate = serde_json::from_str(json_str)?;
-            v48::parse_crate_info(&rustdoc)
-        }
-        46 => {
-            let rustdoc: rustdoc_types_v46::Crate = serde_json::from_str(json_str)?;
-            v46::parse_crate_info(&rustdoc)
-        }
-        _ => Err(anyhow!(
-            "Unsupported rustdoc format version: {}. Supported versions: 46, 48-53",
-            format_version
-        )),
-    }
+/// Whether a rustdoc format version can be deserialized with typed support
+pub fn is_supported_format_version(format_version: u32) -> bool {
+    matches!(format_version, 46 | 48..=LATEST_FORMAT_VERSION)
 }
 
-/// Find and parse a specific item by path based on the rustdoc format version
-pub fn find_item(json_str: &str, item_path: &str) -> Result<String> {
+/// Deserialize rustdoc JSON based on its format version and convert it into
+/// the version-independent model.
+///
+/// Neither deserialization nor conversion can be interrupted, so `cancelled`
+/// is checked before and between them, and the parse stops with
+/// [`DocsError::Cancelled`] at the first check after it is set.
+pub fn parse_crate(json_str: &str, cancelled: &AtomicBool) -> Result<Crate> {
     // First, extract just the format version without full deserialization
     let format_version = get_format_version(json_str)?;
+    check_cancelled(cancelled)?;
 
-    // Dispatch to the appropriate parser based on version
+    // Dispatch to the appropriate conversion based on version. A supported
+    // version that fails to deserialize is a bug worth reporting, not papering
+    // over with a degraded model.
     match format_version {
-        53 => {
-            let rustdoc: rustdoc_types_v53::Crate = serde_json::from_str(json_str)?;
-            v53::find_item(&rustdoc, item_path)
-        }
-        52 => {
-            let rustdoc: rustdoc_types_v52::Crate = serde_json::from_str(json_str)?;
-            v52::find_item(&rustdoc, item_path)
-        }
-        51 => {
-            let rustdoc: rustdoc_types_v51::Crate = serde_json::from_str(json_str)?;
-            v51::find_item(&rustdoc, item_path)
-        }
-        50 => {
-            let rustdoc: rustdoc_types_v50::Crate = serde_json::from_str(json_str)?;
-            v50::find_item(&rustdoc, item_path)
-        }
-        49 => {
-            let rustdoc: rustdoc_types_v49::Crate = serde_json::from_str(json_str)?;
-            v49::find_item(&rustdoc, item_path)
-        }
-        48 => {
-            let rustdoc: rustdoc_types_v48::Crate = serde_json::from_str(json_str)?;
-            v48::find_item(&rustdoc, item_path)
+        57 => parse_typed(json_str, format_version, cancelled, v57::convert),
+        56 => parse_typed(json_str, format_version, cancelled, v56::convert),
+        55 => parse_typed(json_str, format_version, cancelled, v55::convert),
+        54 => parse_typed(json_str, format_version, cancelled, v54::convert),
+        53 => parse_typed(json_str, format_version, cancelled, v53::convert),
+        52 => parse_typed(json_str, format_version, cancelled, v52::convert),
+        51 => parse_typed(json_str, format_version, cancelled, v51::convert),
+        50 => parse_typed(json_str, format_version, cancelled, v50::convert),
+        49 => parse_typed(json_str, format_version, cancelled, v49::convert),
+        48 => parse_typed(json_str, format_version, cancelled, v48::convert),
+        46 => parse_typed(json_str, format_version, cancelled, v46::convert),
+        _ => {
+            // Unknown versions still have a module tree and docs worth showing
+            tracing::warn!(
+                "Unknown rustdoc format version {}; falling back to untyped parsing",
+                format_version
+            );
+            untyped::parse_crate(json_str, cancelled).map_err(|e| {
+                if DocsError::find(&e).is_some() {
+                    return e;
+                }
+                tracing::warn!("Untyped parsing failed: {:#}", e);
+                DocsError::UnsupportedFormat { format_version }.into()
+            })
         }
-        46 => {
-            let rustdoc: rustdoc_types_v46::Crate = serde_json::from_str(json_str)?;
-            v46::find_item(&rustdoc, item_path)
-        }
-        _ => Err(anyhow!(
-            "Unsupported rustdoc format version: {}. Supported versions: 46, 48-53",
+    }
+}
+
+fn parse_typed<T: DeserializeOwned>(
+    json_str: &str,
+    format_version: u32,
+    cancelled: &AtomicBool,
+    convert: fn(T) -> Crate,
+) -> Result<Crate> {
+    let krate = serde_json::from_str(json_str).with_context(|| {
+        format!(
+            "Failed to deserialize rustdoc JSON format version {}",
             format_version
-        )),
+        )
+    })?;
+    check_cancelled(cancelled)?;
+    Ok(convert(krate))
+}
+
+/// Fail with [`DocsError::Cancelled`] once the parse has been given up on
+fn check_cancelled(cancelled: &AtomicBool) -> Result<()> {
+    if cancelled.load(Ordering::Relaxed) {
+        return Err(DocsError::Cancelled.into());
     }
+    Ok(())
 }
@@ -0,0 +1,563 @@
+//! Version-independent model of a rustdoc JSON document.
+//!
+//! Every supported rustdoc format version is converted into these types right
+//! after deserialization, so formatting and search only have to be written once.
+//! The shapes follow `rustdoc_types`, minus details we never render.
+
+// Mirrors rustdoc's data model, so not every field is read by the formatters
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use super::search::SearchIndex;
+
+/// Identifier of an item within a crate's index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Id(pub u32);
+
+#[derive(Debug, Clone)]
+pub struct Crate {
+    /// Id of the root module
+    pub root: Id,
+    pub crate_version: Option<String>,
+    pub includes_private: bool,
+    /// All items local to the crate, plus some re-exported external ones
+    pub index: HashMap<Id, Item>,
+    /// Full paths of items, including those from external crates
+    pub paths: HashMap<Id, ItemSummary>,
+    pub external_crates: HashMap<u32, ExternalCrate>,
+    /// Rustdoc JSON format version this crate was converted from
+    pub format_version: u32,
+    /// Full-text index over the items, built on first search
+    pub search_index: OnceLock<SearchIndex>,
+    /// Every item with each path it can be found under, built on first lookup
+    pub item_paths: OnceLock<Vec<(Id, Vec<String>)>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExternalCrate {
+    pub name: String,
+    pub html_root_url: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ItemSummary {
+    /// 0 for the local crate, otherwise a key into `Crate::external_crates`
+    pub crate_id: u32,
+    pub path: Vec<String>,
+    pub kind: ItemKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub id: Id,
+    pub crate_id: u32,
+    pub name: Option<String>,
+    pub visibility: Visibility,
+    pub docs: Option<String>,
+    /// Intra-doc link targets, keyed by the link text as written in the docs
+    pub links: HashMap<String, Id>,
+    /// Attributes rendered as source text, e.g. `#[non_exhaustive]`
+    pub attrs: Vec<String>,
+    pub deprecation: Option<Deprecation>,
+    pub inner: ItemEnum,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Visibility {
+    Public,
+    /// Implied visibility, e.g. of trait items and enum variants
+    Default,
+    Crate,
+    Restricted {
+        parent: Id,
+        path: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Deprecation {
+    pub since: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemKind {
+    Module,
+    ExternCrate,
+    Use,
+    Struct,
+    StructField,
+    Union,
+    Enum,
+    Variant,
+    Function,
+    TypeAlias,
+    Constant,
+    Trait,
+    TraitAlias,
+    Impl,
+    Static,
+    ExternType,
+    Macro,
+    ProcAttribute,
+    ProcDerive,
+    AssocConst,
+    AssocType,
+    Primitive,
+    Keyword,
+    /// A builtin attribute, documented by the core library
+    Attribute,
+}
+
+impl ItemKind {
+    /// Human readable name of the kind
+    pub fn name(self) -> &'static str {
+        match self {
+            ItemKind::Module => "Module",
+            ItemKind::ExternCrate => "External Crate",
+            ItemKind::Use => "Import",
+            ItemKind::Struct => "Struct",
+            ItemKind::StructField => "Struct Field",
+            ItemKind::Union => "Union",
+            ItemKind::Enum => "Enum",
+            ItemKind::Variant => "Enum Variant",
+            ItemKind::Function => "Function",
+            ItemKind::TypeAlias => "Type Alias",
+            ItemKind::Constant => "Constant",
+            ItemKind::Trait => "Trait",
+            ItemKind::TraitAlias => "Trait Alias",
+            ItemKind::Impl => "Implementation",
+            ItemKind::Static => "Static",
+            ItemKind::ExternType => "External Type",
+            ItemKind::Macro => "Macro",
+            ItemKind::ProcAttribute => "Attribute Macro",
+            ItemKind::ProcDerive => "Derive Macro",
+            ItemKind::AssocConst => "Associated Constant",
+            ItemKind::AssocType => "Associated Type",
+            ItemKind::Primitive => "Primitive",
+            ItemKind::Keyword => "Keyword",
+            ItemKind::Attribute => "Attribute",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ItemEnum {
+    Module(Module),
+    ExternCrate {
+        name: String,
+        rename: Option<String>,
+    },
+    Use(Use),
+    Union(Union),
+    Struct(Struct),
+    StructField(Type),
+    Enum(Enum),
+    Variant(Variant),
+    Function(Function),
+    Trait(Trait),
+    TraitAlias(TraitAlias),
+    Impl(Impl),
+    TypeAlias(TypeAlias),
+    Constant {
+        type_: Type,
+        const_: Constant,
+    },
+    Static(Static),
+    ExternType,
+    Macro(String),
+    ProcMacro(ProcMacro),
+    Primitive(Primitive),
+    AssocConst {
+        type_: Type,
+        value: Option<String>,
+    },
+    AssocType {
+        generics: Generics,
+        bounds: Vec<GenericBound>,
+        type_: Option<Type>,
+    },
+    /// An item whose details could not be decoded, from an unsupported format version
+    Opaque(ItemKind),
+}
+
+impl ItemEnum {
+    pub fn kind(&self) -> ItemKind {
+        match self {
+            ItemEnum::Module(_) => ItemKind::Module,
+            ItemEnum::ExternCrate { .. } => ItemKind::ExternCrate,
+            ItemEnum::Use(_) => ItemKind::Use,
+            ItemEnum::Union(_) => ItemKind::Union,
+            ItemEnum::Struct(_) => ItemKind::Struct,
+            ItemEnum::StructField(_) => ItemKind::StructField,
+            ItemEnum::Enum(_) => ItemKind::Enum,
+            ItemEnum::Variant(_) => ItemKind::Variant,
+            ItemEnum::Function(_) => ItemKind::Function,
+            ItemEnum::Trait(_) => ItemKind::Trait,
+            ItemEnum::TraitAlias(_) => ItemKind::TraitAlias,
+            ItemEnum::Impl(_) => ItemKind::Impl,
+            ItemEnum::TypeAlias(_) => ItemKind::TypeAlias,
+            ItemEnum::Constant { .. } => ItemKind::Constant,
+            ItemEnum::Static(_) => ItemKind::Static,
+            ItemEnum::ExternType => ItemKind::ExternType,
+            ItemEnum::Macro(_) => ItemKind::Macro,
+            ItemEnum::ProcMacro(proc_macro) => match proc_macro.kind {
+                MacroKind::Bang => ItemKind::Macro,
+                MacroKind::Attr => ItemKind::ProcAttribute,
+                MacroKind::Derive => ItemKind::ProcDerive,
+            },
+            ItemEnum::Primitive(_) => ItemKind::Primitive,
+            ItemEnum::AssocConst { .. } => ItemKind::AssocConst,
+            ItemEnum::AssocType { .. } => ItemKind::AssocType,
+            ItemEnum::Opaque(kind) => *kind,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub is_crate: bool,
+    pub items: Vec<Id>,
+    pub is_stripped: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Union {
+    pub generics: Generics,
+    pub has_stripped_fields: bool,
+    pub fields: Vec<Id>,
+    pub impls: Vec<Id>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Struct {
+    pub kind: StructKind,
+    pub generics: Generics,
+    pub impls: Vec<Id>,
+}
+
+#[derive(Debug, Clone)]
+pub enum StructKind {
+    Unit,
+    /// Field ids, `None` for fields stripped from the docs
+    Tuple(Vec<Option<Id>>),
+    Plain {
+        fields: Vec<Id>,
+        has_stripped_fields: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Enum {
+    pub generics: Generics,
+    pub has_stripped_variants: bool,
+    pub variants: Vec<Id>,
+    pub impls: Vec<Id>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub kind: VariantKind,
+    pub discriminant: Option<Discriminant>,
+}
+
+#[derive(Debug, Clone)]
+pub enum VariantKind {
+    Plain,
+    /// Field ids, `None` for fields stripped from the docs
+    Tuple(Vec<Option<Id>>),
+    Struct {
+        fields: Vec<Id>,
+        has_stripped_fields: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Discriminant {
+    /// The expression as written in the source
+    pub expr: String,
+    /// The evaluated value
+    pub value: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub sig: FunctionSignature,
+    pub generics: Generics,
+    pub header: FunctionHeader,
+    pub has_body: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionSignature {
+    /// Parameter names (patterns) and types
+    pub inputs: Vec<(String, Type)>,
+    pub output: Option<Type>,
+    pub is_c_variadic: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionHeader {
+    pub is_const: bool,
+    pub is_unsafe: bool,
+    pub is_async: bool,
+    /// ABI string like `"C"` or `"system"`, `None` for the Rust ABI
+    pub abi: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Generics {
+    pub params: Vec<GenericParamDef>,
+    pub where_predicates: Vec<WherePredicate>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GenericParamDef {
+    pub name: String,
+    pub kind: GenericParamDefKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum GenericParamDefKind {
+    Lifetime {
+        outlives: Vec<String>,
+    },
+    Type {
+        bounds: Vec<GenericBound>,
+        default: Option<Type>,
+        /// Desugared from `impl Trait` in argument position
+        is_synthetic: bool,
+    },
+    Const {
+        type_: Type,
+        default: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
+pub enum WherePredicate {
+    BoundPredicate {
+        type_: Type,
+        bounds: Vec<GenericBound>,
+        /// Higher-ranked parameters, e.g. `for<'a>`
+        generic_params: Vec<GenericParamDef>,
+    },
+    LifetimePredicate {
+        lifetime: String,
+        outlives: Vec<String>,
+    },
+    EqPredicate {
+        lhs: Type,
+        rhs: Term,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum GenericBound {
+    TraitBound {
+        trait_: Path,
+        /// Higher-ranked parameters, e.g. `for<'a>`
+        generic_params: Vec<GenericParamDef>,
+        modifier: TraitBoundModifier,
+    },
+    Outlives(String),
+    /// Precise capturing, e.g. `use<'a, T>`
+    Use(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraitBoundModifier {
+    None,
+    Maybe,
+    MaybeConst,
+}
+
+#[derive(Debug, Clone)]
+pub struct Path {
+    /// The path as written, e.g. `Vec` or `std::io::Result`
+    pub path: String,
+    pub id: Id,
+    pub args: Option<Box<GenericArgs>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum GenericArgs {
+    AngleBracketed {
+        args: Vec<GenericArg>,
+        constraints: Vec<AssocItemConstraint>,
+    },
+    Parenthesized {
+        inputs: Vec<Type>,
+        output: Option<Type>,
+    },
+    /// `T::method(..)`
+    ReturnTypeNotation,
+}
+
+#[derive(Debug, Clone)]
+pub enum GenericArg {
+    Lifetime(String),
+    Type(Type),
+    Const(Constant),
+    Infer,
+}
+
+#[derive(Debug, Clone)]
+pub struct AssocItemConstraint {
+    pub name: String,
+    pub binding: AssocItemConstraintKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum AssocItemConstraintKind {
+    Equality(Term),
+    Constraint(Vec<GenericBound>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Term {
+    Type(Type),
+    Constant(Constant),
+}
+
+#[derive(Debug, Clone)]
+pub struct Constant {
+    pub expr: String,
+    pub value: Option<String>,
+    pub is_literal: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Type {
+    ResolvedPath(Path),
+    DynTrait(DynTrait),
+    Generic(String),
+    Primitive(String),
+    FunctionPointer(Box<FunctionPointer>),
+    Tuple(Vec<Type>),
+    Slice(Box<Type>),
+    Array {
+        type_: Box<Type>,
+        len: String,
+    },
+    /// Pattern type, e.g. `u32 is 1..`
+    Pat {
+        type_: Box<Type>,
+    },
+    ImplTrait(Vec<GenericBound>),
+    Infer,
+    RawPointer {
+        is_mutable: bool,
+        type_: Box<Type>,
+    },
+    BorrowedRef {
+        lifetime: Option<String>,
+        is_mutable: bool,
+        type_: Box<Type>,
+    },
+    /// Associated type projection, e.g. `<T as Iterator>::Item`
+    QualifiedPath {
+        name: String,
+        self_type: Box<Type>,
+        trait_: Option<Path>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct DynTrait {
+    pub traits: Vec<PolyTrait>,
+    pub lifetime: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PolyTrait {
+    pub trait_: Path,
+    pub generic_params: Vec<GenericParamDef>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionPointer {
+    pub sig: FunctionSignature,
+    pub generic_params: Vec<GenericParamDef>,
+    pub header: FunctionHeader,
+}
+
+#[derive(Debug, Clone)]
+pub struct Trait {
+    pub is_auto: bool,
+    pub is_unsafe: bool,
+    pub is_dyn_compatible: bool,
+    pub items: Vec<Id>,
+    pub generics: Generics,
+    pub bounds: Vec<GenericBound>,
+    /// Impl blocks implementing this trait
+    pub implementations: Vec<Id>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TraitAlias {
+    pub generics: Generics,
+    pub params: Vec<GenericBound>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Impl {
+    pub is_unsafe: bool,
+    pub generics: Generics,
+    /// Names of trait methods not overridden by this impl
+    pub provided_trait_methods: Vec<String>,
+    pub trait_: Option<Path>,
+    pub for_: Type,
+    pub items: Vec<Id>,
+    pub is_negative: bool,
+    /// Compiler-generated auto trait impl, e.g. `Send`
+    pub is_synthetic: bool,
+    /// The generic type this blanket impl applies to, e.g. `T` in `impl<T> From<T> for T`
+    pub blanket_impl: Option<Type>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeAlias {
+    pub type_: Type,
+    pub generics: Generics,
+}
+
+#[derive(Debug, Clone)]
+pub struct Static {
+    pub type_: Type,
+    pub is_mutable: bool,
+    pub expr: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Use {
+    /// Full path being imported, as written
+    pub source: String,
+    /// Name under which the item is imported
+    pub name: String,
+    /// Imported item, `None` for primitives and unresolved imports
+    pub id: Option<Id>,
+    pub is_glob: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcMacro {
+    pub kind: MacroKind,
+    pub helpers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacroKind {
+    Bang,
+    Attr,
+    Derive,
+}
+
+#[derive(Debug, Clone)]
+pub struct Primitive {
+    pub name: String,
+    pub impls: Vec<Id>,
+}
@@ -0,0 +1,255 @@
+//! Following `pub use` items to what they re-export.
+//!
+//! Facade crates expose most of their API through re-exports, so module
+//! listings and item lookup work on the exports of a module rather than its
+//! raw items: plain items as they are, imports replaced by their targets, and
+//! glob imports expanded into the public items of the module or enum they
+//! name. Targets in other crates can't be followed past `Crate::paths`, which
+//! still gives their kind and original path.
+
+use std::collections::HashSet;
+
+use super::model::{Crate, Id, Item, ItemEnum, ItemKind, Visibility};
+
+/// Longest chain of imports followed before giving up, e.g. `a::X` re-exporting
+/// `b::X` re-exporting `c::X`
+const MAX_IMPORT_CHAIN: usize = 16;
+
+/// A public name of a module along with what it refers to
+#[derive(Debug, Clone)]
+pub struct Export<'a> {
+    /// Name the item is exported under
+    pub name: String,
+    pub kind: ItemKind,
+    /// The item, unless it's defined in another crate and wasn't inlined.
+    /// Imports that couldn't be followed at all are kept as the `Use` item.
+    pub item: Option<&'a Item>,
+    /// Where a re-exported item is defined, `None` for items defined in the module
+    pub origin: Option<Vec<String>>,
+}
+
+/// The public names of a module, with imports followed to their targets
+pub fn module_exports<'a>(krate: &'a Crate, module_id: &Id) -> Vec<Export<'a>> {
+    collect_exports(krate, module_id, &mut HashSet::from([*module_id]))
+}
+
+fn collect_exports<'a>(
+    krate: &'a Crate,
+    module_id: &Id,
+    globbed: &mut HashSet<Id>,
+) -> Vec<Export<'a>> {
+    let items = match krate.index.get(module_id).map(|item| &item.inner) {
+        Some(ItemEnum::Module(module)) => module.items.as_slice(),
+        _ => return Vec::new(),
+    };
+
+    let mut exports = Vec::new();
+    let mut globs = Vec::new();
+    for item in items.iter().filter_map(|id| krate.index.get(id)) {
+        if !matches!(item.visibility, Visibility::Public) {
+            continue;
+        }
+        match &item.inner {
+            ItemEnum::Use(import) if import.is_glob => globs.push(item),
+            ItemEnum::Use(import) => exports.push(follow_import(krate, item, &import.name)),
+            _ => {
+                if let Some(name) = &item.name {
+                    exports.push(Export {
+                        name: name.clone(),
+                        kind: item.inner.kind(),
+                        item: Some(item),
+                        origin: None,
+                    });
+                }
+            }
+        }
+    }
+
+    // Names defined or imported explicitly shadow those from glob imports
+    let mut names: HashSet<(String, bool)> = exports
+        .iter()
+        .map(|export| (export.name.clone(), is_macro(export.kind)))
+        .collect();
+    for glob in globs {
+        for export in expand_glob(krate, glob, globbed) {
+            if names.insert((export.name.clone(), is_macro(export.kind))) {
+                exports.push(export);
+            }
+        }
+    }
+
+    exports
+}
+
+/// Macros live in their own namespace, so they don't clash with types or functions
+fn is_macro(kind: ItemKind) -> bool {
+    matches!(
+        kind,
+        ItemKind::Macro | ItemKind::ProcAttribute | ItemKind::ProcDerive
+    )
+}
+
+/// The original path of an item, from `paths` or failing that the import source
+fn origin_path(krate: &Crate, id: &Id, source: &str) -> Vec<String> {
+    match krate.paths.get(id) {
+        Some(summary) => summary.path.clone(),
+        None => source.split("::").map(str::to_string).collect(),
+    }
+}
+
+/// Follow a single import, and any imports it points to, to the item it names
+fn follow_import<'a>(krate: &'a Crate, use_item: &'a Item, name: &str) -> Export<'a> {
+    let unresolved = Export {
+        name: name.to_string(),
+        kind: ItemKind::Use,
+        item: Some(use_item),
+        origin: None,
+    };
+
+    let mut current = use_item;
+    for _ in 0..MAX_IMPORT_CHAIN {
+        let ItemEnum::Use(import) = &current.inner else {
+            break;
+        };
+        let Some(target_id) = import.id else {
+            return unresolved;
+        };
+
+        match krate.index.get(&target_id) {
+            Some(target) if matches!(target.inner, ItemEnum::Use(_)) => current = target,
+            Some(target) => {
+                return Export {
+                    name: name.to_string(),
+                    kind: target.inner.kind(),
+                    item: Some(target),
+                    origin: Some(origin_path(krate, &target_id, &import.source)),
+                }
+            }
+            // Defined in another crate, so only its path is known
+            None => {
+                return match krate.paths.get(&target_id) {
+                    Some(summary) => Export {
+                        name: name.to_string(),
+                        kind: summary.kind,
+                        item: None,
+                        origin: Some(summary.path.clone()),
+                    },
+                    None => unresolved,
+                }
+            }
+        }
+    }
+
+    unresolved
+}
+
+/// Expand a glob import into the public names of the module or enum it names
+fn expand_glob<'a>(
+    krate: &'a Crate,
+    use_item: &'a Item,
+    globbed: &mut HashSet<Id>,
+) -> Vec<Export<'a>> {
+    let ItemEnum::Use(import) = &use_item.inner else {
+        return Vec::new();
+    };
+    let unresolved = || {
+        vec![Export {
+            name: format!("{}::*", import.source),
+            kind: ItemKind::Use,
+            item: Some(use_item),
+            origin: None,
+        }]
+    };
+
+    let Some(target_id) = import.id else {
+        return unresolved();
+    };
+    let exports = match krate.index.get(&target_id).map(|item| &item.inner) {
+        Some(ItemEnum::Module(_)) => {
+            // Glob imports can form cycles, so skip modules already being expanded
+            if !globbed.insert(target_id) {
+                return Vec::new();
+            }
+            let exports = collect_exports(krate, &target_id, globbed);
+            globbed.remove(&target_id);
+            exports
+        }
+        Some(ItemEnum::Enum(enum_data)) => enum_data
+            .variants
+            .iter()
+            .filter_map(|id| krate.index.get(id))
+            .filter_map(|variant| {
+                Some(Export {
+                    name: variant.name.clone()?,
+                    kind: ItemKind::Variant,
+                    item: Some(variant),
+                    origin: None,
+                })
+            })
+            .collect(),
+        _ => return unresolved(),
+    };
+
+    exports
+        .into_iter()
+        .map(|mut export| {
+            if export.origin.is_none() && export.kind != ItemKind::Use {
+                export.origin = export.item.map(|item| match krate.paths.get(&item.id) {
+                    Some(summary) => summary.path.clone(),
+                    None => {
+                        let mut path = origin_path(krate, &target_id, &import.source);
+                        path.push(export.name.clone());
+                        path
+                    }
+                });
+            }
+            export
+        })
+        .collect()
+}
+
+/// Every re-exported item reachable from the crate root, with the public path
+/// it's exported under, including the contents of re-exported modules. Items
+/// only reachable under their own path aren't included.
+pub fn reexports(krate: &Crate) -> Vec<(Vec<String>, Export<'_>)> {
+    let root_name = krate
+        .index
+        .get(&krate.root)
+        .and_then(|root| root.name.clone())
+        .unwrap_or_default();
+
+    let mut found = Vec::new();
+    // A module is walked at most twice: under its own path, and under the
+    // first path it's re-exported as
+    let mut visited = HashSet::new();
+    let mut stack = vec![(krate.root, vec![root_name])];
+    while let Some((module_id, module_path)) = stack.pop() {
+        let in_reexport = krate
+            .paths
+            .get(&module_id)
+            .is_some_and(|summary| summary.path != module_path);
+        if !visited.insert((module_id, in_reexport)) {
+            continue;
+        }
+
+        for mut export in module_exports(krate, &module_id) {
+            let mut path = module_path.clone();
+            path.push(export.name.clone());
+
+            if let (ItemKind::Module, Some(item)) = (export.kind, export.item) {
+                stack.push((item.id, path.clone()));
+            }
+            if export.origin.is_none() {
+                export.origin = export
+                    .item
+                    .and_then(|item| krate.paths.get(&item.id))
+                    .map(|summary| summary.path.clone());
+            }
+            if export.origin.as_ref().is_some_and(|origin| *origin != path) {
+                found.push((path, export));
+            }
+        }
+    }
+
+    found
+}
@@ -0,0 +1,438 @@
+//! Resolution of user-supplied item paths like `struct.Widget`, `fn.make` or
+//! `widgets::Widget::new` to items in a crate.
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+use crate::error::DocsError;
+
+use super::external::{self, ExternalItem};
+use super::model::{Crate, Id, Item, ItemEnum, ItemKind, StructKind};
+use super::reexport;
+
+/// A parsed item path: optional kind filter plus `::`-separated segments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemQuery {
+    /// Kinds the item must have, empty to accept any
+    pub kinds: Vec<ItemKind>,
+    pub segments: Vec<String>,
+}
+
+impl ItemQuery {
+    /// Parse `kind.path::to::Item` or `kind:path::to::Item`, where the kind
+    /// prefix is optional and uses the names from docs.rs URLs (`struct`,
+    /// `fn`, `trait`, `mod`, ...)
+    pub fn parse(item_path: &str) -> Result<Self> {
+        let item_path = item_path.trim();
+        let (kinds, path) = match split_kind_prefix(item_path) {
+            Some((kinds, rest)) => (kinds.to_vec(), rest.to_string()),
+            // Without a kind, dots are separators, e.g. `Widget.new`
+            None => (Vec::new(), item_path.replace('.', "::")),
+        };
+
+        let segments: Vec<String> = path
+            .trim_start_matches("::")
+            .split("::")
+            .map(|segment| segment.trim().to_string())
+            .collect();
+        if segments.iter().any(String::is_empty) {
+            return Err(
+                DocsError::InvalidArgument(format!("Invalid item path '{}'", item_path)).into(),
+            );
+        }
+
+        Ok(Self { kinds, segments })
+    }
+
+    /// Whether the query accepts items of this kind
+    pub fn matches_kind(&self, kind: ItemKind) -> bool {
+        self.kinds.is_empty() || self.kinds.contains(&kind)
+    }
+
+    /// Whether an item of this kind under this path matches the query
+    pub fn matches(&self, path: &[String], kind: ItemKind) -> bool {
+        self.matches_kind(kind) && ends_with_segments(path, &self.segments)
+    }
+}
+
+/// Split a kind prefix like `fn.` or `fn:` off an item path
+fn split_kind_prefix(item_path: &str) -> Option<(&'static [ItemKind], &str)> {
+    let end = item_path.find(['.', ':'])?;
+    let rest = &item_path[end + 1..];
+    if rest.starts_with(':') {
+        return None;
+    }
+    Some((kinds_for_prefix(&item_path[..end])?, rest))
+}
+
+/// Map a docs.rs URL kind prefix to the item kinds it covers
+pub fn kinds_for_prefix(prefix: &str) -> Option<&'static [ItemKind]> {
+    let kinds: &[ItemKind] = match prefix {
+        "mod" | "module" => &[ItemKind::Module],
+        "struct" => &[ItemKind::Struct],
+        "union" => &[ItemKind::Union],
+        "enum" => &[ItemKind::Enum],
+        "variant" => &[ItemKind::Variant],
+        "structfield" | "field" => &[ItemKind::StructField],
+        "fn" | "function" | "method" | "tymethod" => &[ItemKind::Function],
+        "trait" => &[ItemKind::Trait],
+        "traitalias" => &[ItemKind::TraitAlias],
+        "type" | "typealias" => &[ItemKind::TypeAlias, ItemKind::AssocType],
+        "associatedtype" => &[ItemKind::AssocType],
+        "const" | "constant" => &[ItemKind::Constant, ItemKind::AssocConst],
+        "associatedconstant" => &[ItemKind::AssocConst],
+        "static" => &[ItemKind::Static],
+        "macro" => &[
+            ItemKind::Macro,
+            ItemKind::ProcAttribute,
+            ItemKind::ProcDerive,
+        ],
+        "attr" => &[ItemKind::ProcAttribute],
+        "derive" => &[ItemKind::ProcDerive],
+        "primitive" => &[ItemKind::Primitive],
+        "keyword" => &[ItemKind::Keyword],
+        "attribute" => &[ItemKind::Attribute],
+        "externcrate" => &[ItemKind::ExternCrate],
+        _ => return None,
+    };
+    Some(kinds)
+}
+
+/// An item matching a query, along with the path it was found under
+#[derive(Debug, Clone)]
+pub struct Candidate<'a> {
+    pub item: &'a Item,
+    pub path: Vec<String>,
+    pub kind: ItemKind,
+}
+
+/// Outcome of resolving a query
+#[derive(Debug)]
+pub enum Resolution<'a> {
+    Found(Candidate<'a>),
+    /// Several items match equally well, best first
+    Ambiguous(Vec<Candidate<'a>>),
+}
+
+/// What an item path refers to, resolved once for every way a lookup is shown
+#[derive(Debug)]
+pub enum Lookup<'a> {
+    /// Items of the crate itself
+    Local(Resolution<'a>),
+    /// An item of another crate, which the crate only knows by path
+    External(ExternalItem),
+}
+
+/// Every documented item in the crate with its full path, including
+/// associated items, enum variants and struct fields, which rustdoc doesn't
+/// list in `paths`, and items re-exported under another path.
+///
+/// Walking the re-exports is expensive on large crates, so the paths are
+/// collected on first use and kept with the crate.
+pub fn item_paths(krate: &Crate) -> impl Iterator<Item = (&Item, &[String])> {
+    krate
+        .item_paths
+        .get_or_init(|| collect_item_paths(krate))
+        .iter()
+        .filter_map(|(id, path)| Some((krate.index.get(id)?, path.as_slice())))
+}
+
+fn collect_item_paths(krate: &Crate) -> Vec<(Id, Vec<String>)> {
+    let mut items = Vec::new();
+
+    for (id, summary) in &krate.paths {
+        let Some(item) = krate.index.get(id) else {
+            continue;
+        };
+        items.push((*id, summary.path.clone()));
+
+        let mut push_child = |child_id: &Id| {
+            // Some children, like enum variants, have a path of their own
+            if krate.paths.contains_key(child_id) {
+                return;
+            }
+            if let Some(child) = krate.index.get(child_id) {
+                if let Some(name) = &child.name {
+                    let mut path = summary.path.clone();
+                    path.push(name.clone());
+                    items.push((child.id, path));
+                }
+            }
+        };
+
+        match &item.inner {
+            ItemEnum::Struct(struct_data) => {
+                if let StructKind::Plain { fields, .. } = &struct_data.kind {
+                    fields.iter().for_each(&mut push_child);
+                }
+                inherent_and_trait_items(krate, &struct_data.impls).for_each(push_child);
+            }
+            ItemEnum::Union(union_data) => {
+                union_data.fields.iter().for_each(&mut push_child);
+                inherent_and_trait_items(krate, &union_data.impls).for_each(push_child);
+            }
+            ItemEnum::Enum(enum_data) => {
+                enum_data.variants.iter().for_each(&mut push_child);
+                inherent_and_trait_items(krate, &enum_data.impls).for_each(push_child);
+            }
+            ItemEnum::Primitive(primitive) => {
+                inherent_and_trait_items(krate, &primitive.impls).for_each(push_child);
+            }
+            ItemEnum::Trait(trait_data) => trait_data.items.iter().for_each(push_child),
+            _ => {}
+        }
+    }
+
+    for (path, export) in reexport::reexports(krate) {
+        if let Some(item) = export.item {
+            items.push((item.id, path));
+        }
+    }
+
+    items
+}
+
+/// Items of the impls written for a type, leaving out auto trait and blanket impls
+pub(super) fn inherent_and_trait_items<'a>(
+    krate: &'a Crate,
+    impl_ids: &'a [Id],
+) -> impl Iterator<Item = &'a Id> {
+    impl_ids
+        .iter()
+        .filter_map(|id| match &krate.index.get(id)?.inner {
+            ItemEnum::Impl(impl_data)
+                if !impl_data.is_synthetic && impl_data.blanket_impl.is_none() =>
+            {
+                Some(&impl_data.items)
+            }
+            _ => None,
+        })
+        .flatten()
+}
+
+/// Whether `segments` matches the end of `path` segment by segment
+fn ends_with_segments(path: &[String], segments: &[String]) -> bool {
+    path.len() >= segments.len() && path[path.len() - segments.len()..] == *segments
+}
+
+/// Resolve a query to a single item, or to the ranked candidates if several
+/// match equally well
+pub fn resolve_item<'a>(krate: &'a Crate, item_path: &str) -> Result<Resolution<'a>> {
+    let query = ItemQuery::parse(item_path)?;
+
+    let mut candidates: Vec<Candidate> = item_paths(krate)
+        .filter(|(item, path)| query.matches(path, item.inner.kind()))
+        .map(|(item, path)| Candidate {
+            kind: item.inner.kind(),
+            item,
+            path: path.to_vec(),
+        })
+        .collect();
+
+    // Items that aren't reachable through `paths`, e.g. from stripped modules
+    if candidates.is_empty() && query.segments.len() == 1 {
+        let name = &query.segments[0];
+        candidates = krate
+            .index
+            .values()
+            .filter(|item| {
+                item.name.as_ref() == Some(name) && query.matches_kind(item.inner.kind())
+            })
+            .map(|item| Candidate {
+                kind: item.inner.kind(),
+                item,
+                path: vec![name.clone()],
+            })
+            .collect();
+    }
+
+    candidates.sort_by(|a, b| {
+        rank(krate, a, &query)
+            .cmp(&rank(krate, b, &query))
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.item.id.cmp(&b.item.id))
+    });
+    // An item can match under both its own path and a re-export, keep the best
+    let mut seen = HashSet::new();
+    candidates.retain(|candidate| seen.insert(candidate.item.id));
+
+    // Only pick a candidate if it's clearly better than the rest, e.g. the one
+    // type named `Error` rather than an `Error` variant
+    let is_unique_best = match candidates.as_slice() {
+        [] => {
+            return Err(DocsError::ItemNotFound {
+                kind: "Item",
+                path: item_path.to_string(),
+            }
+            .into())
+        }
+        [_] => true,
+        [first, second, ..] => tier(krate, first, &query) < tier(krate, second, &query),
+    };
+
+    if is_unique_best {
+        Ok(Resolution::Found(candidates.remove(0)))
+    } else {
+        Ok(Resolution::Ambiguous(candidates))
+    }
+}
+
+/// Resolve a path to items of the crate, or else to an item of one of its
+/// dependencies
+pub fn lookup_item<'a>(krate: &'a Crate, item_path: &str) -> Result<Lookup<'a>> {
+    match resolve_item(krate, item_path) {
+        Ok(resolution) => Ok(Lookup::Local(resolution)),
+        Err(e) => external::locate(krate, item_path)?
+            .map(Lookup::External)
+            .ok_or(e),
+    }
+}
+
+/// How well a candidate matches: full-path matches first, then items of this
+/// crate, then items with their own path before associated items and fields
+fn tier(krate: &Crate, candidate: &Candidate, query: &ItemQuery) -> (bool, bool, bool) {
+    (
+        candidate.path != query.segments,
+        candidate.item.crate_id != 0,
+        !krate.paths.contains_key(&candidate.item.id),
+    )
+}
+
+/// Sort key for candidates: their tier, then shorter paths
+fn rank(krate: &Crate, candidate: &Candidate, query: &ItemQuery) -> (bool, bool, bool, usize) {
+    let (full_path, external, associated) = tier(krate, candidate, query);
+    (full_path, external, associated, candidate.path.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rustdoc_parser::fixture::{enum_of, opaque, trait_of, TestCrate};
+
+    fn path(candidate: &Candidate) -> String {
+        candidate.path.join("::")
+    }
+
+    fn found(krate: &Crate, item_path: &str) -> String {
+        match resolve_item(krate, item_path).unwrap() {
+            Resolution::Found(candidate) => path(&candidate),
+            Resolution::Ambiguous(candidates) => panic!(
+                "'{}' is ambiguous: {:?}",
+                item_path,
+                candidates.iter().map(path).collect::<Vec<_>>()
+            ),
+        }
+    }
+
+    fn ambiguous(krate: &Crate, item_path: &str) -> Vec<(String, ItemKind)> {
+        match resolve_item(krate, item_path).unwrap() {
+            Resolution::Found(candidate) => {
+                panic!("'{}' resolved to {}", item_path, path(&candidate))
+            }
+            Resolution::Ambiguous(candidates) => candidates
+                .iter()
+                .map(|candidate| (path(candidate), candidate.kind))
+                .collect(),
+        }
+    }
+
+    /// `widgets::Widget` and `widgets::widgets::Widget`, a `make` function and
+    /// module, and `Error` as a struct, a variant and an associated type
+    fn widgets() -> Crate {
+        let mut krate = TestCrate::new("widgets");
+        krate.item("Widget", opaque(ItemKind::Struct));
+        krate.item("widgets::Widget", opaque(ItemKind::Struct));
+        krate.item("make", opaque(ItemKind::Function));
+        krate.item("shapes::make", opaque(ItemKind::Module));
+        krate.item("Error", opaque(ItemKind::Struct));
+        let variant = krate.child("Error", opaque(ItemKind::Variant));
+        krate.item("Kind", enum_of(vec![variant]));
+        let assoc = krate.child("Error", opaque(ItemKind::AssocType));
+        krate.item("Parse", trait_of(vec![assoc]));
+        krate.build()
+    }
+
+    #[test]
+    fn parse_splits_kind_prefixes_and_path_segments() {
+        let query = ItemQuery::parse("struct.widgets::Widget").unwrap();
+        assert_eq!(query.kinds, vec![ItemKind::Struct]);
+        assert_eq!(query.segments, vec!["widgets", "Widget"]);
+
+        let query = ItemQuery::parse("fn:make").unwrap();
+        assert_eq!(query.kinds, vec![ItemKind::Function]);
+        assert_eq!(query.segments, vec!["make"]);
+
+        let query = ItemQuery::parse("::widgets::Widget").unwrap();
+        assert!(query.kinds.is_empty());
+        assert_eq!(query.segments, vec!["widgets", "Widget"]);
+
+        assert!(ItemQuery::parse("widgets::::Widget").is_err());
+        assert!(ItemQuery::parse("fn.").is_err());
+    }
+
+    #[test]
+    fn parse_treats_dots_without_a_kind_as_separators() {
+        let query = ItemQuery::parse("Widget.new").unwrap();
+        assert!(query.kinds.is_empty());
+        assert_eq!(query.segments, vec!["Widget", "new"]);
+        assert_eq!(
+            ItemQuery::parse("widgets.Widget.new").unwrap().segments,
+            vec!["widgets", "Widget", "new"]
+        );
+    }
+
+    #[test]
+    fn full_path_beats_suffix_match() {
+        let krate = widgets();
+        assert_eq!(found(&krate, "widgets::Widget"), "widgets::Widget");
+        assert_eq!(
+            found(&krate, "widgets::widgets::Widget"),
+            "widgets::widgets::Widget"
+        );
+        assert_eq!(found(&krate, "widgets.Widget"), "widgets::Widget");
+    }
+
+    #[test]
+    fn matches_whole_segments_only() {
+        let krate = widgets();
+        assert!(resolve_item(&krate, "idget").is_err());
+        assert!(resolve_item(&krate, "gets::Widget").is_err());
+    }
+
+    #[test]
+    fn kind_prefixes_filter_candidates() {
+        let krate = widgets();
+        assert_eq!(
+            ambiguous(&krate, "make"),
+            vec![
+                ("widgets::make".to_string(), ItemKind::Function),
+                ("widgets::shapes::make".to_string(), ItemKind::Module),
+            ]
+        );
+        assert_eq!(found(&krate, "fn.make"), "widgets::make");
+        assert_eq!(found(&krate, "fn:make"), "widgets::make");
+        assert_eq!(found(&krate, "mod:make"), "widgets::shapes::make");
+        assert!(resolve_item(&krate, "struct:make").is_err());
+    }
+
+    #[test]
+    fn items_with_their_own_path_beat_associated_items() {
+        let krate = widgets();
+        assert_eq!(found(&krate, "Error"), "widgets::Error");
+        assert_eq!(found(&krate, "variant.Error"), "widgets::Kind::Error");
+        assert_eq!(found(&krate, "Parse::Error"), "widgets::Parse::Error");
+    }
+
+    #[test]
+    fn ambiguous_results_list_candidates_best_first() {
+        let krate = widgets();
+        assert_eq!(
+            ambiguous(&krate, "Widget"),
+            vec![
+                ("widgets::Widget".to_string(), ItemKind::Struct),
+                ("widgets::widgets::Widget".to_string(), ItemKind::Struct),
+            ]
+        );
+    }
+}
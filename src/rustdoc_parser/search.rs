@@ -0,0 +1,266 @@
+//! Full-text search over the items of a crate.
+//!
+//! Item names, module paths and doc text are split into lowercase terms
+//! (`HttpProxy` and `http_proxy` both give `http` and `proxy`) with a crude
+//! plural stemmer, and stored in an inverted index that is built on the first
+//! search and kept with the crate.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+
+use crate::error::DocsError;
+
+use super::format::doc_summary;
+use super::model::{Crate, Id, ItemKind, Visibility};
+use super::resolve;
+
+/// Weight of a term occurring in the item's own name
+const NAME_WEIGHT: f32 = 5.0;
+/// Weight of a term occurring in the path of the item's parent module or type
+const PATH_WEIGHT: f32 = 1.5;
+/// Weight of a term occurring in the item's docs, scaled by frequency
+const DOCS_WEIGHT: f32 = 1.0;
+/// Extra score when the whole query equals the item name
+const EXACT_NAME_BONUS: f32 = 10.0;
+
+#[derive(Debug, Clone)]
+struct Entry {
+    id: Id,
+    path: Vec<String>,
+    kind: ItemKind,
+}
+
+/// Inverted index from terms to the items containing them
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    entries: Vec<Entry>,
+    /// Term to (entry index, weight) postings
+    postings: HashMap<String, Vec<(usize, f32)>>,
+}
+
+/// A search hit, best first
+#[derive(Debug, Clone)]
+pub struct SearchHit<'a> {
+    pub id: Id,
+    pub path: &'a [String],
+    pub kind: ItemKind,
+    pub score: f32,
+}
+
+impl SearchIndex {
+    /// Index every public item of a crate, including associated items and fields
+    pub fn build(krate: &Crate) -> Self {
+        let mut index = SearchIndex::default();
+        let mut seen = HashSet::new();
+
+        for (item, path) in resolve::item_paths(krate) {
+            if !matches!(item.visibility, Visibility::Public | Visibility::Default) {
+                continue;
+            }
+            // Re-exports come after the item's own path, which is the one indexed
+            if !seen.insert(item.id) {
+                continue;
+            }
+
+            let mut weights: HashMap<String, f32> = HashMap::new();
+            if let Some((name, parents)) = path.split_last() {
+                for term in tokenize(name) {
+                    *weights.entry(term).or_default() += NAME_WEIGHT;
+                }
+                for term in parents.iter().flat_map(|segment| tokenize(segment)) {
+                    *weights.entry(term).or_default() += PATH_WEIGHT;
+                }
+            }
+            if let Some(docs) = &item.docs {
+                let mut counts: HashMap<String, u32> = HashMap::new();
+                for term in tokenize(docs) {
+                    *counts.entry(term).or_default() += 1;
+                }
+                for (term, count) in counts {
+                    *weights.entry(term).or_default() += DOCS_WEIGHT * (1.0 + (count as f32).ln());
+                }
+            }
+
+            let entry = index.entries.len();
+            index.entries.push(Entry {
+                id: item.id,
+                path: path.to_vec(),
+                kind: item.inner.kind(),
+            });
+            for (term, weight) in weights {
+                index
+                    .postings
+                    .entry(term)
+                    .or_default()
+                    .push((entry, weight));
+            }
+        }
+
+        index
+    }
+
+    /// Rank items against a query, keeping those accepted by `filter`.
+    ///
+    /// Each query term contributes its weight in the item times its inverse
+    /// document frequency, and items matching only some of the terms are
+    /// scaled down accordingly.
+    pub fn search(
+        &self,
+        query: &str,
+        filter: impl Fn(&[String], ItemKind) -> bool,
+    ) -> Vec<SearchHit<'_>> {
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let total = self.entries.len() as f32;
+        let mut scores: HashMap<usize, (f32, usize)> = HashMap::new();
+        for term in &terms {
+            let Some(postings) = self.postings.get(term) else {
+                continue;
+            };
+            let idf = (1.0 + total / postings.len() as f32).ln();
+            for &(entry, weight) in postings {
+                let score = scores.entry(entry).or_default();
+                score.0 += weight * idf;
+                score.1 += 1;
+            }
+        }
+
+        let query_name = query.trim().to_lowercase();
+        let mut hits: Vec<SearchHit> = scores
+            .into_iter()
+            .filter_map(|(entry, (score, matched))| {
+                let entry = &self.entries[entry];
+                if !filter(&entry.path, entry.kind) {
+                    return None;
+                }
+                let mut score = score * matched as f32 / terms.len() as f32;
+                if entry
+                    .path
+                    .last()
+                    .is_some_and(|name| name.to_lowercase() == query_name)
+                {
+                    score += EXACT_NAME_BONUS;
+                }
+                Some(SearchHit {
+                    id: entry.id,
+                    path: &entry.path,
+                    kind: entry.kind,
+                    score,
+                })
+            })
+            .collect();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.path.len().cmp(&b.path.len()))
+                .then_with(|| a.path.cmp(b.path))
+        });
+        hits
+    }
+}
+
+/// Split text into lowercase search terms, breaking identifiers at
+/// underscores and case changes
+fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let mut current = String::new();
+        let mut prev_lower = false;
+        for c in word.chars() {
+            if c.is_uppercase() && prev_lower && !current.is_empty() {
+                terms.push(std::mem::take(&mut current));
+            }
+            prev_lower = c.is_lowercase() || c.is_numeric();
+            current.extend(c.to_lowercase());
+        }
+        if !current.is_empty() {
+            terms.push(current);
+        }
+    }
+
+    terms
+        .into_iter()
+        .filter(|term| term.len() > 1)
+        .map(|term| stem(&term))
+        .collect()
+}
+
+/// Reduce plurals to their singular so `proxies` finds `Proxy`
+fn stem(term: &str) -> String {
+    if let Some(base) = term.strip_suffix("ies").filter(|base| base.len() > 1) {
+        format!("{}y", base)
+    } else if let Some(base) = term
+        .strip_suffix('s')
+        .filter(|base| base.len() > 2 && !base.ends_with('s'))
+    {
+        base.to_string()
+    } else {
+        term.to_string()
+    }
+}
+
+/// Whether an item path lies under a module prefix like `blocking` or
+/// `reqwest::blocking`, with or without the crate name
+fn in_module(path: &[String], module: &[String]) -> bool {
+    path.starts_with(module) || path.get(1..).is_some_and(|rest| rest.starts_with(module))
+}
+
+/// Search a crate's items, optionally restricted to a kind (`struct`, `fn`, ...)
+/// and a module prefix, and format the top `limit` hits
+pub fn search_items(
+    krate: &Crate,
+    query: &str,
+    kind: Option<&str>,
+    module: Option<&str>,
+    limit: usize,
+) -> Result<String> {
+    let kinds = match kind {
+        Some(kind) => resolve::kinds_for_prefix(kind)
+            .ok_or_else(|| DocsError::InvalidArgument(format!("Unknown item kind '{}'", kind)))?
+            .to_vec(),
+        None => Vec::new(),
+    };
+    let module: Vec<String> = module
+        .map(|module| module.split("::").map(str::to_string).collect())
+        .unwrap_or_default();
+
+    let index = krate.search_index.get_or_init(|| SearchIndex::build(krate));
+    let hits = index.search(query, |path, item_kind| {
+        (kinds.is_empty() || kinds.contains(&item_kind)) && in_module(path, &module)
+    });
+
+    if hits.is_empty() {
+        return Ok(format!("No items found matching '{}'", query));
+    }
+
+    let mut lines = vec![format!(
+        "# Search results for '{}'\n\nShowing {} of {} matching items\n",
+        query,
+        hits.len().min(limit),
+        hits.len()
+    )];
+    for hit in hits.iter().take(limit) {
+        let summary = krate
+            .index
+            .get(&hit.id)
+            .map(doc_summary)
+            .unwrap_or_default();
+        lines.push(format!(
+            "- **{}** `{}` (score {:.1}){}",
+            hit.kind.name(),
+            hit.path.join("::"),
+            hit.score,
+            summary
+        ));
+    }
+
+    Ok(lines.join("\n"))
+}
@@ -0,0 +1,757 @@
+//! Pretty-printing of types, generics and function signatures as Rust source.
+//!
+//! The output follows rustfmt's layout closely enough to be pasted into code:
+//! long parameter lists are broken one per line and where-clauses go on their
+//! own lines.
+
+use super::model::{
+    AssocItemConstraint, AssocItemConstraintKind, Constant, Function, FunctionHeader,
+    FunctionSignature, GenericArg, GenericArgs, GenericBound, GenericParamDef, GenericParamDefKind,
+    Impl, Path, Term, TraitBoundModifier, Type, Visibility, WherePredicate,
+};
+
+/// Signatures longer than this have their parameters split over several lines
+const MAX_LINE_WIDTH: usize = 100;
+
+/// Render a function's full declaration, e.g. `pub fn get<K>(&self, key: K) -> Option<&V>`
+pub fn format_function_signature(name: &str, visibility: &Visibility, func: &Function) -> String {
+    let prefix = format_function_prefix(name, visibility, func);
+    let output = format_output(&func.sig.output);
+
+    let params = format_params(&func.sig);
+    let one_line = format!("{}({}){}", prefix, params.join(", "), output);
+
+    let mut signature = if one_line.len() <= MAX_LINE_WIDTH || params.is_empty() {
+        one_line
+    } else {
+        let params: String = params
+            .iter()
+            .map(|param| format!("    {},\n", param))
+            .collect();
+        format!("{}(\n{}){}", prefix, params, output)
+    };
+
+    signature.push_str(&format_where_clause(&func.generics.where_predicates));
+    signature
+}
+
+/// Render a function's declaration on a single line, for use in lists
+pub fn format_function_signature_inline(
+    name: &str,
+    visibility: &Visibility,
+    func: &Function,
+) -> String {
+    format!(
+        "{}({}){}{}",
+        format_function_prefix(name, visibility, func),
+        format_params(&func.sig).join(", "),
+        format_output(&func.sig.output),
+        format_where_clause_inline(&func.generics.where_predicates)
+    )
+}
+
+/// Render everything up to the parameter list, e.g. `pub async fn get<K>`
+fn format_function_prefix(name: &str, visibility: &Visibility, func: &Function) -> String {
+    format!(
+        "{}{}fn {}{}",
+        format_visibility(visibility),
+        format_header(&func.header),
+        name,
+        format_generic_params(&func.generics.params)
+    )
+}
+
+/// Render an impl block header on a single line, e.g. `impl<T: Clone> Trait for Foo<T>`
+pub fn format_impl_header(impl_data: &Impl) -> String {
+    let mut header = String::new();
+    if impl_data.is_unsafe {
+        header.push_str("unsafe ");
+    }
+    header.push_str("impl");
+    header.push_str(&format_generic_params(&impl_data.generics.params));
+    header.push(' ');
+    if let Some(trait_) = &impl_data.trait_ {
+        if impl_data.is_negative {
+            header.push('!');
+        }
+        header.push_str(&format_path(trait_));
+        header.push_str(" for ");
+    }
+    // Blanket impls read as `impl<T> From<T> for T` rather than naming the type
+    let for_ = impl_data.blanket_impl.as_ref().unwrap_or(&impl_data.for_);
+    header.push_str(&format_type(for_));
+    header.push_str(&format_where_clause_inline(
+        &impl_data.generics.where_predicates,
+    ));
+    header
+}
+
+/// Render a visibility as a prefix, including the trailing space
+pub fn format_visibility(visibility: &Visibility) -> String {
+    match visibility {
+        Visibility::Public => "pub ".to_string(),
+        Visibility::Default => String::new(),
+        Visibility::Crate => "pub(crate) ".to_string(),
+        Visibility::Restricted { path, .. } => format!("pub(in {}) ", path),
+    }
+}
+
+/// Render the qualifiers before `fn`, e.g. `const unsafe extern "C" `
+fn format_header(header: &FunctionHeader) -> String {
+    let mut out = String::new();
+    if header.is_const {
+        out.push_str("const ");
+    }
+    if header.is_async {
+        out.push_str("async ");
+    }
+    if header.is_unsafe {
+        out.push_str("unsafe ");
+    }
+    if let Some(abi) = &header.abi {
+        out.push_str(&format!("extern {:?} ", abi));
+    }
+    out
+}
+
+fn format_params(sig: &FunctionSignature) -> Vec<String> {
+    let mut params: Vec<String> = sig
+        .inputs
+        .iter()
+        .map(|(name, type_)| format_param(name, type_))
+        .collect();
+    if sig.is_c_variadic {
+        params.push("...".to_string());
+    }
+    params
+}
+
+/// Render a parameter, using the shorthand forms for `self` receivers
+fn format_param(name: &str, type_: &Type) -> String {
+    if name == "self" {
+        match type_ {
+            Type::Generic(self_type) if self_type == "Self" => return "self".to_string(),
+            Type::BorrowedRef {
+                lifetime,
+                is_mutable,
+                type_,
+            } if matches!(type_.as_ref(), Type::Generic(s) if s == "Self") => {
+                return format!("&{}self", format_ref_qualifiers(lifetime, *is_mutable));
+            }
+            _ => {}
+        }
+    }
+    format!("{}: {}", name, format_type(type_))
+}
+
+fn format_output(output: &Option<Type>) -> String {
+    match output {
+        Some(Type::Tuple(types)) if types.is_empty() => String::new(),
+        Some(type_) => format!(" -> {}", format_type(type_)),
+        None => String::new(),
+    }
+}
+
+/// Render generic parameter declarations, e.g. `<'a, T: Clone = u8, const N: usize>`.
+///
+/// Parameters desugared from `impl Trait` arguments are left out, as they
+/// already show up in the parameter types.
+pub fn format_generic_params(params: &[GenericParamDef]) -> String {
+    let params: Vec<String> = params
+        .iter()
+        .filter(|param| {
+            !matches!(
+                param.kind,
+                GenericParamDefKind::Type {
+                    is_synthetic: true,
+                    ..
+                }
+            )
+        })
+        .map(format_generic_param)
+        .collect();
+
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", params.join(", "))
+    }
+}
+
+fn format_generic_param(param: &GenericParamDef) -> String {
+    match &param.kind {
+        GenericParamDefKind::Lifetime { outlives } => {
+            if outlives.is_empty() {
+                param.name.clone()
+            } else {
+                format!("{}: {}", param.name, outlives.join(" + "))
+            }
+        }
+        GenericParamDefKind::Type {
+            bounds, default, ..
+        } => {
+            let mut out = param.name.clone();
+            if !bounds.is_empty() {
+                out.push_str(&format!(": {}", format_bounds(bounds)));
+            }
+            if let Some(default) = default {
+                out.push_str(&format!(" = {}", format_type(default)));
+            }
+            out
+        }
+        GenericParamDefKind::Const { type_, default } => {
+            let mut out = format!("const {}: {}", param.name, format_type(type_));
+            if let Some(default) = default {
+                out.push_str(&format!(" = {}", default));
+            }
+            out
+        }
+    }
+}
+
+/// Render a higher-ranked binder like `for<'a> `, including the trailing space
+fn format_higher_ranked(params: &[GenericParamDef]) -> String {
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("for{} ", format_generic_params(params))
+    }
+}
+
+/// Render a where-clause on its own lines, or nothing if there are no predicates
+pub fn format_where_clause(predicates: &[WherePredicate]) -> String {
+    if predicates.is_empty() {
+        return String::new();
+    }
+
+    let predicates: String = predicates
+        .iter()
+        .map(|predicate| format!("    {},\n", format_where_predicate(predicate)))
+        .collect();
+    format!("\nwhere\n{}", predicates.trim_end())
+}
+
+/// Render a where-clause on the same line, e.g. ` where T: Clone`
+pub fn format_where_clause_inline(predicates: &[WherePredicate]) -> String {
+    if predicates.is_empty() {
+        return String::new();
+    }
+
+    let predicates: Vec<String> = predicates.iter().map(format_where_predicate).collect();
+    format!(" where {}", predicates.join(", "))
+}
+
+fn format_where_predicate(predicate: &WherePredicate) -> String {
+    match predicate {
+        WherePredicate::BoundPredicate {
+            type_,
+            bounds,
+            generic_params,
+        } => format!(
+            "{}{}: {}",
+            format_higher_ranked(generic_params),
+            format_type(type_),
+            format_bounds(bounds)
+        ),
+        WherePredicate::LifetimePredicate { lifetime, outlives } => {
+            format!("{}: {}", lifetime, outlives.join(" + "))
+        }
+        WherePredicate::EqPredicate { lhs, rhs } => {
+            format!("{} = {}", format_type(lhs), format_term(rhs))
+        }
+    }
+}
+
+/// Render a `+`-separated list of bounds, e.g. `Clone + Send + 'static`
+pub fn format_bounds(bounds: &[GenericBound]) -> String {
+    bounds
+        .iter()
+        .map(format_bound)
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+fn format_bound(bound: &GenericBound) -> String {
+    match bound {
+        GenericBound::TraitBound {
+            trait_,
+            generic_params,
+            modifier,
+        } => {
+            let modifier = match modifier {
+                TraitBoundModifier::None => "",
+                TraitBoundModifier::Maybe => "?",
+                TraitBoundModifier::MaybeConst => "~const ",
+            };
+            format!(
+                "{}{}{}",
+                format_higher_ranked(generic_params),
+                modifier,
+                format_path(trait_)
+            )
+        }
+        GenericBound::Outlives(lifetime) => lifetime.clone(),
+        GenericBound::Use(args) => format!("use<{}>", args.join(", ")),
+    }
+}
+
+/// Render a path with its generic arguments, e.g. `HashMap<K, V>`
+pub fn format_path(path: &Path) -> String {
+    // Paths written by derive macros start with `$crate::`
+    let name = path.path.strip_prefix("$crate::").unwrap_or(&path.path);
+    match &path.args {
+        Some(args) => format!("{}{}", name, format_generic_args(args)),
+        None => name.to_string(),
+    }
+}
+
+fn format_generic_args(args: &GenericArgs) -> String {
+    match args {
+        GenericArgs::AngleBracketed { args, constraints } => {
+            let args: Vec<String> = args
+                .iter()
+                .map(format_generic_arg)
+                .chain(constraints.iter().map(format_constraint))
+                .collect();
+            if args.is_empty() {
+                String::new()
+            } else {
+                format!("<{}>", args.join(", "))
+            }
+        }
+        GenericArgs::Parenthesized { inputs, output } => {
+            let inputs: Vec<String> = inputs.iter().map(format_type).collect();
+            format!("({}){}", inputs.join(", "), format_output(output))
+        }
+        GenericArgs::ReturnTypeNotation => "(..)".to_string(),
+    }
+}
+
+fn format_generic_arg(arg: &GenericArg) -> String {
+    match arg {
+        GenericArg::Lifetime(lifetime) => lifetime.clone(),
+        GenericArg::Type(type_) => format_type(type_),
+        GenericArg::Const(constant) => format_constant(constant),
+        GenericArg::Infer => "_".to_string(),
+    }
+}
+
+fn format_constraint(constraint: &AssocItemConstraint) -> String {
+    match &constraint.binding {
+        AssocItemConstraintKind::Equality(term) => {
+            format!("{} = {}", constraint.name, format_term(term))
+        }
+        AssocItemConstraintKind::Constraint(bounds) => {
+            format!("{}: {}", constraint.name, format_bounds(bounds))
+        }
+    }
+}
+
+fn format_term(term: &Term) -> String {
+    match term {
+        Term::Type(type_) => format_type(type_),
+        Term::Constant(constant) => format_constant(constant),
+    }
+}
+
+fn format_constant(constant: &Constant) -> String {
+    constant.expr.clone()
+}
+
+/// Render a type as it would be written in source
+pub fn format_type(type_: &Type) -> String {
+    match type_ {
+        Type::ResolvedPath(path) => format_path(path),
+        Type::DynTrait(dyn_trait) => {
+            let mut bounds: Vec<String> = dyn_trait
+                .traits
+                .iter()
+                .map(|poly| {
+                    format!(
+                        "{}{}",
+                        format_higher_ranked(&poly.generic_params),
+                        format_path(&poly.trait_)
+                    )
+                })
+                .collect();
+            bounds.extend(dyn_trait.lifetime.clone());
+            format!("dyn {}", bounds.join(" + "))
+        }
+        Type::Generic(name) | Type::Primitive(name) => name.clone(),
+        Type::FunctionPointer(fn_pointer) => {
+            let params = format_params(&fn_pointer.sig).join(", ");
+            format!(
+                "{}{}fn({}){}",
+                format_higher_ranked(&fn_pointer.generic_params),
+                format_header(&fn_pointer.header),
+                params,
+                format_output(&fn_pointer.sig.output)
+            )
+        }
+        Type::Tuple(types) => match types.as_slice() {
+            [single] => format!("({},)", format_type(single)),
+            types => format!(
+                "({})",
+                types.iter().map(format_type).collect::<Vec<_>>().join(", ")
+            ),
+        },
+        Type::Slice(type_) => format!("[{}]", format_type(type_)),
+        Type::Array { type_, len } => format!("[{}; {}]", format_type(type_), len),
+        Type::Pat { type_ } => format_type(type_),
+        Type::ImplTrait(bounds) => format!("impl {}", format_bounds(bounds)),
+        Type::Infer => "_".to_string(),
+        Type::RawPointer { is_mutable, type_ } => format!(
+            "*{} {}",
+            if *is_mutable { "mut" } else { "const" },
+            format_pointee(type_)
+        ),
+        Type::BorrowedRef {
+            lifetime,
+            is_mutable,
+            type_,
+        } => format!(
+            "&{}{}",
+            format_ref_qualifiers(lifetime, *is_mutable),
+            format_pointee(type_)
+        ),
+        Type::QualifiedPath {
+            name,
+            self_type,
+            trait_,
+        } => match trait_ {
+            // `T::Item` is how these are written for generic parameters
+            Some(trait_) if !matches!(self_type.as_ref(), Type::Generic(_)) => format!(
+                "<{} as {}>::{}",
+                format_type(self_type),
+                format_path(trait_),
+                name
+            ),
+            _ => format!("{}::{}", format_type(self_type), name),
+        },
+    }
+}
+
+/// Render the `'a mut ` part of a reference
+fn format_ref_qualifiers(lifetime: &Option<String>, is_mutable: bool) -> String {
+    let mut out = String::new();
+    if let Some(lifetime) = lifetime {
+        out.push_str(lifetime);
+        out.push(' ');
+    }
+    if is_mutable {
+        out.push_str("mut ");
+    }
+    out
+}
+
+/// Render the target of a reference or pointer, parenthesizing trait objects
+/// with several bounds like `&(dyn Error + Send)`
+fn format_pointee(type_: &Type) -> String {
+    let needs_parens = match type_ {
+        Type::DynTrait(dyn_trait) => {
+            dyn_trait.traits.len() + usize::from(dyn_trait.lifetime.is_some()) > 1
+        }
+        Type::ImplTrait(bounds) => bounds.len() > 1,
+        _ => false,
+    };
+
+    if needs_parens {
+        format!("({})", format_type(type_))
+    } else {
+        format_type(type_)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::model::{DynTrait, Generics, Id, PolyTrait};
+    use super::*;
+
+    fn path(name: &str, args: Vec<Type>) -> Path {
+        Path {
+            path: name.to_string(),
+            id: Id(0),
+            args: (!args.is_empty()).then(|| {
+                Box::new(GenericArgs::AngleBracketed {
+                    args: args.into_iter().map(GenericArg::Type).collect(),
+                    constraints: Vec::new(),
+                })
+            }),
+        }
+    }
+
+    fn named(name: &str) -> Type {
+        Type::ResolvedPath(path(name, Vec::new()))
+    }
+
+    fn named_with(name: &str, args: Vec<Type>) -> Type {
+        Type::ResolvedPath(path(name, args))
+    }
+
+    fn generic(name: &str) -> Type {
+        Type::Generic(name.to_string())
+    }
+
+    fn reference(lifetime: Option<&str>, is_mutable: bool, type_: Type) -> Type {
+        Type::BorrowedRef {
+            lifetime: lifetime.map(str::to_string),
+            is_mutable,
+            type_: Box::new(type_),
+        }
+    }
+
+    fn trait_bound(name: &str) -> GenericBound {
+        GenericBound::TraitBound {
+            trait_: path(name, Vec::new()),
+            generic_params: Vec::new(),
+            modifier: TraitBoundModifier::None,
+        }
+    }
+
+    fn lifetime_param(name: &str) -> GenericParamDef {
+        GenericParamDef {
+            name: name.to_string(),
+            kind: GenericParamDefKind::Lifetime {
+                outlives: Vec::new(),
+            },
+        }
+    }
+
+    fn type_param(name: &str, bounds: Vec<GenericBound>) -> GenericParamDef {
+        GenericParamDef {
+            name: name.to_string(),
+            kind: GenericParamDefKind::Type {
+                bounds,
+                default: None,
+                is_synthetic: false,
+            },
+        }
+    }
+
+    fn function(inputs: Vec<(&str, Type)>, output: Option<Type>, generics: Generics) -> Function {
+        Function {
+            sig: FunctionSignature {
+                inputs: inputs
+                    .into_iter()
+                    .map(|(name, type_)| (name.to_string(), type_))
+                    .collect(),
+                output,
+                is_c_variadic: false,
+            },
+            generics,
+            header: FunctionHeader {
+                is_const: false,
+                is_unsafe: false,
+                is_async: false,
+                abi: None,
+            },
+            has_body: true,
+        }
+    }
+
+    fn signature(name: &str, func: &Function) -> String {
+        format_function_signature(name, &Visibility::Public, func)
+    }
+
+    #[test]
+    fn self_receivers_use_shorthand() {
+        let func = function(
+            vec![
+                ("self", reference(None, false, generic("Self"))),
+                ("key", generic("K")),
+            ],
+            Some(named("bool")),
+            Generics::default(),
+        );
+        assert_eq!(signature("has", &func), "pub fn has(&self, key: K) -> bool");
+
+        let func = function(
+            vec![("self", reference(Some("'a"), true, generic("Self")))],
+            None,
+            Generics::default(),
+        );
+        assert_eq!(signature("clear", &func), "pub fn clear(&'a mut self)");
+
+        let func = function(vec![("self", generic("Self"))], None, Generics::default());
+        assert_eq!(signature("into_inner", &func), "pub fn into_inner(self)");
+
+        // `self: Box<Self>` has no shorthand
+        let func = function(
+            vec![("self", named_with("Box", vec![generic("Self")]))],
+            None,
+            Generics::default(),
+        );
+        assert_eq!(signature("boxed", &func), "pub fn boxed(self: Box<Self>)");
+    }
+
+    #[test]
+    fn trait_objects_with_several_bounds_are_parenthesized_behind_pointers() {
+        let dyn_error = |lifetime: Option<&str>, traits: &[&str]| {
+            Type::DynTrait(DynTrait {
+                traits: traits
+                    .iter()
+                    .map(|name| PolyTrait {
+                        trait_: path(name, Vec::new()),
+                        generic_params: Vec::new(),
+                    })
+                    .collect(),
+                lifetime: lifetime.map(str::to_string),
+            })
+        };
+
+        assert_eq!(
+            format_type(&reference(None, false, dyn_error(None, &["Error", "Send"]))),
+            "&(dyn Error + Send)"
+        );
+        assert_eq!(
+            format_type(&reference(
+                None,
+                false,
+                dyn_error(Some("'static"), &["Error"])
+            )),
+            "&(dyn Error + 'static)"
+        );
+        assert_eq!(
+            format_type(&reference(None, true, dyn_error(None, &["Error"]))),
+            "&mut dyn Error"
+        );
+        assert_eq!(
+            format_type(&named_with(
+                "Box",
+                vec![dyn_error(None, &["Error", "Send"])]
+            )),
+            "Box<dyn Error + Send>"
+        );
+        assert_eq!(
+            format_type(&Type::RawPointer {
+                is_mutable: false,
+                type_: Box::new(Type::ImplTrait(vec![
+                    trait_bound("Read"),
+                    trait_bound("Send")
+                ])),
+            }),
+            "*const (impl Read + Send)"
+        );
+    }
+
+    #[test]
+    fn where_clauses_go_on_their_own_lines() {
+        let generics = Generics {
+            params: vec![type_param("T", Vec::new()), lifetime_param("'a")],
+            where_predicates: vec![
+                WherePredicate::BoundPredicate {
+                    type_: generic("T"),
+                    bounds: vec![trait_bound("Clone"), trait_bound("Send")],
+                    generic_params: Vec::new(),
+                },
+                WherePredicate::LifetimePredicate {
+                    lifetime: "'a".to_string(),
+                    outlives: vec!["'static".to_string()],
+                },
+            ],
+        };
+        let func = function(vec![("value", generic("T"))], None, generics);
+        assert_eq!(
+            signature("store", &func),
+            "pub fn store<T, 'a>(value: T)\nwhere\n    T: Clone + Send,\n    'a: 'static,"
+        );
+        assert_eq!(
+            format_function_signature_inline("store", &Visibility::Public, &func),
+            "pub fn store<T, 'a>(value: T) where T: Clone + Send, 'a: 'static"
+        );
+    }
+
+    #[test]
+    fn higher_ranked_bounds_get_a_for_binder() {
+        let fn_bound = GenericBound::TraitBound {
+            trait_: Path {
+                path: "Fn".to_string(),
+                id: Id(0),
+                args: Some(Box::new(GenericArgs::Parenthesized {
+                    inputs: vec![reference(
+                        Some("'a"),
+                        false,
+                        Type::Primitive("str".to_string()),
+                    )],
+                    output: Some(reference(
+                        Some("'a"),
+                        false,
+                        Type::Primitive("str".to_string()),
+                    )),
+                })),
+            },
+            generic_params: vec![lifetime_param("'a")],
+            modifier: TraitBoundModifier::None,
+        };
+        assert_eq!(
+            format_bounds(std::slice::from_ref(&fn_bound)),
+            "for<'a> Fn(&'a str) -> &'a str"
+        );
+
+        let predicate = WherePredicate::BoundPredicate {
+            type_: generic("F"),
+            bounds: vec![fn_bound],
+            generic_params: Vec::new(),
+        };
+        assert_eq!(
+            format_where_clause_inline(&[predicate]),
+            " where F: for<'a> Fn(&'a str) -> &'a str"
+        );
+
+        let predicate = WherePredicate::BoundPredicate {
+            type_: reference(Some("'a"), false, generic("T")),
+            bounds: vec![trait_bound("IntoIterator")],
+            generic_params: vec![lifetime_param("'a")],
+        };
+        assert_eq!(
+            format_where_clause_inline(&[predicate]),
+            " where for<'a> &'a T: IntoIterator"
+        );
+    }
+
+    #[test]
+    fn qualified_paths_name_the_trait_unless_the_type_is_generic() {
+        let qualified = |self_type: Type| Type::QualifiedPath {
+            name: "Item".to_string(),
+            self_type: Box::new(self_type),
+            trait_: Some(path("IntoIterator", Vec::new())),
+        };
+        assert_eq!(
+            format_type(&qualified(named_with(
+                "Vec",
+                vec![Type::Primitive("u8".into())]
+            ))),
+            "<Vec<u8> as IntoIterator>::Item"
+        );
+        assert_eq!(format_type(&qualified(generic("T"))), "T::Item");
+        assert_eq!(
+            format_type(&Type::QualifiedPath {
+                name: "Output".to_string(),
+                self_type: Box::new(generic("Self")),
+                trait_: None,
+            }),
+            "Self::Output"
+        );
+    }
+
+    #[test]
+    fn long_signatures_put_one_parameter_per_line() {
+        let inputs: Vec<(&str, Type)> = ["first_parameter", "second_parameter", "third_parameter"]
+            .into_iter()
+            .map(|name| (name, named("std::collections::HashMap")))
+            .collect();
+        let func = function(inputs, Some(named("bool")), Generics::default());
+        let one_line = format_function_signature_inline("compare", &Visibility::Public, &func);
+        assert!(one_line.len() > MAX_LINE_WIDTH);
+        assert_eq!(
+            signature("compare", &func),
+            "pub fn compare(\n    \
+             first_parameter: std::collections::HashMap,\n    \
+             second_parameter: std::collections::HashMap,\n    \
+             third_parameter: std::collections::HashMap,\n) -> bool"
+        );
+
+        let short = function(vec![("x", named("u8"))], None, Generics::default());
+        assert_eq!(signature("short", &short), "pub fn short(x: u8)");
+    }
+}
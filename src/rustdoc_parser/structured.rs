@@ -0,0 +1,640 @@
+//! Lookup results as serializable data, for clients that would rather read
+//! JSON than Markdown.
+//!
+//! These hold everything a lookup shows: kind, path, signature, docs, fields,
+//! variants and impls, with nested members summarized by the first line of
+//! their docs. The Markdown in `format` is rendered from them, so both forms
+//! always show the same thing.
+
+use anyhow::{anyhow, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use super::examples;
+use super::external::ExternalItem;
+use super::format::{
+    assoc_item_declaration, format_variant_declaration, get_first_line, is_non_exhaustive,
+    resolve_fields,
+};
+use super::links::{self, LinkTarget};
+use super::model::{
+    Crate, Deprecation, Id, Impl, Item, ItemEnum, StructKind, Trait, VariantKind, Visibility,
+};
+use super::reexport::{self, Export};
+use super::resolve::{Candidate, Lookup, Resolution};
+use super::signature;
+
+/// A crate's root documentation and public items
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CrateDoc {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Crate-level documentation, with intra-doc links rewritten to full paths
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+    /// Public items of the crate root, including re-exports
+    pub items: Vec<ExportDoc>,
+    /// Items linked from the documentation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<ReferenceDoc>,
+}
+
+/// A public name of a module
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportDoc {
+    pub name: String,
+    pub kind: String,
+    /// Where the item is defined, for re-exports
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reexport_of: Option<String>,
+    /// First line of the item's documentation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// Outcome of looking up an item path
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemLookup {
+    /// The item, when the path names exactly one and docs were requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item: Option<ItemDoc>,
+    /// The dependency the item was found in, when it isn't defined in the
+    /// crate that was looked up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defined_in: Option<CrateRef>,
+    /// Code examples from the documentation, when examples were requested
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<ExampleDoc>,
+    /// Items matching an ambiguous path, best match first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<CandidateDoc>,
+}
+
+/// A crate and the version of it that was used
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CrateRef {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// Full documentation of an item
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemDoc {
+    pub name: String,
+    pub kind: String,
+    /// Path the item was found under
+    pub path: String,
+    /// Where the item is defined, when found through a re-export
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reexport_of: Option<String>,
+    /// Link to the item's rustdoc page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docs_url: Option<String>,
+    /// Visibility, for items that aren't public, e.g. `pub(crate)`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<String>,
+    /// Declaration of functions, associated items, variants and fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Documentation, with intra-doc links rewritten to full paths
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecation: Option<DeprecationDoc>,
+    /// Whether the item is marked `#[non_exhaustive]`
+    pub non_exhaustive: bool,
+    /// How a struct's fields are declared
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub struct_kind: Option<StructKindDoc>,
+    /// Public fields of a struct or union
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldDoc>,
+    /// Whether some fields are private or hidden from the documentation
+    pub has_hidden_fields: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<VariantDoc>,
+    /// Whether some variants of an enum are hidden from the documentation
+    pub has_hidden_variants: bool,
+    /// Qualifiers of a trait, `auto` and `unsafe`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub qualifiers: Vec<String>,
+    /// Associated items of a trait
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trait_items: Vec<AssocItemDoc>,
+    /// Number of impl blocks of a trait in its crate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub implementor_count: Option<usize>,
+    /// Impl blocks of a type, inherent impls first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub impls: Vec<ImplDoc>,
+    /// Items linked from the documentation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<ReferenceDoc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StructKindDoc {
+    /// `struct Name { field: Type }`
+    Plain,
+    /// `struct Name(Type);`
+    Tuple,
+    /// `struct Name;`
+    Unit,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeprecationDoc {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldDoc {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantDoc {
+    pub name: String,
+    /// The variant as declared, e.g. `Move { x: i32, y: i32 }`
+    pub declaration: String,
+    pub non_exhaustive: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Fields of a struct variant
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldDoc>,
+}
+
+/// A method, associated constant or associated type
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AssocItemDoc {
+    pub name: String,
+    pub kind: String,
+    pub declaration: String,
+    /// Whether a trait method has a default implementation
+    pub provided: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImplKind {
+    Inherent,
+    Trait,
+    /// Compiler-generated impl of an auto trait like `Send`
+    Auto,
+    /// Generic impl like `impl<T> From<T> for T`
+    Blanket,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImplDoc {
+    /// The impl header, e.g. `impl<T: Clone> Clone for Wrapper<T>`
+    pub header: String,
+    pub kind: ImplKind,
+    /// Public associated items, listed for inherent and trait impls
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<AssocItemDoc>,
+    /// Trait methods the impl gets from their default implementations
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provided_methods: Vec<String>,
+}
+
+/// An item linked from documentation
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceDoc {
+    pub path: String,
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docs_url: Option<String>,
+}
+
+/// An item matching an ambiguous path
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateDoc {
+    pub path: String,
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// A code example from documentation
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExampleDoc {
+    /// Path of the item whose docs the example was found in
+    pub owner: String,
+    /// Rustdoc attributes of the code block, e.g. `no_run`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    pub code: String,
+}
+
+fn summary(item: &Item) -> Option<String> {
+    item.docs.as_deref().map(get_first_line)
+}
+
+fn reference_doc(target: LinkTarget) -> ReferenceDoc {
+    ReferenceDoc {
+        path: target.path.join("::"),
+        kind: target.kind.name().to_string(),
+        docs_url: target.docs_url,
+    }
+}
+
+pub(super) fn export_doc(export: &Export) -> ExportDoc {
+    let (name, reexport_of) = match export.item.map(|item| &item.inner) {
+        Some(ItemEnum::Use(import)) if import.is_glob => {
+            (format!("{}::*", import.source), Some(import.source.clone()))
+        }
+        Some(ItemEnum::Use(import)) => (import.name.clone(), Some(import.source.clone())),
+        _ => (
+            export.name.clone(),
+            export.origin.as_ref().map(|origin| origin.join("::")),
+        ),
+    };
+    ExportDoc {
+        name,
+        kind: export.kind.name().to_string(),
+        reexport_of,
+        summary: export.item.and_then(summary),
+    }
+}
+
+/// Describe the crate root: its docs and public items
+pub fn crate_doc(krate: &Crate) -> Result<CrateDoc> {
+    let root_item = krate
+        .index
+        .get(&krate.root)
+        .ok_or_else(|| anyhow!("Root item '{}' not found in index", krate.root.0))?;
+
+    let docs = links::render_docs(krate, root_item);
+    let items = reexport::module_exports(krate, &krate.root)
+        .iter()
+        .map(export_doc)
+        .collect();
+
+    Ok(CrateDoc {
+        name: root_item.name.clone().unwrap_or_default(),
+        version: krate.crate_version.clone(),
+        docs: docs.as_ref().map(|docs| docs.text.clone()),
+        items,
+        references: docs
+            .map(|docs| docs.references.into_iter().map(reference_doc).collect())
+            .unwrap_or_default(),
+    })
+}
+
+pub(super) fn candidate_docs(candidates: &[Candidate]) -> Vec<CandidateDoc> {
+    candidates
+        .iter()
+        .map(|candidate| CandidateDoc {
+            path: candidate.path.join("::"),
+            kind: candidate.kind.name().to_string(),
+            summary: summary(candidate.item),
+        })
+        .collect()
+}
+
+/// Describe a looked up item, or list the candidates if its path is ambiguous
+pub fn describe_lookup(krate: &Crate, lookup: &Lookup) -> ItemLookup {
+    match lookup {
+        Lookup::Local(Resolution::Found(candidate)) => ItemLookup {
+            item: Some(item_doc(krate, candidate.item, &candidate.path)),
+            ..ItemLookup::default()
+        },
+        Lookup::Local(Resolution::Ambiguous(candidates)) => ItemLookup {
+            candidates: candidate_docs(candidates),
+            ..ItemLookup::default()
+        },
+        Lookup::External(item) => external_lookup(item),
+    }
+}
+
+/// Collect the code examples from a looked up item's documentation
+pub fn describe_examples(krate: &Crate, lookup: &Lookup) -> ItemLookup {
+    let candidate = match lookup {
+        Lookup::Local(Resolution::Found(candidate)) => candidate,
+        Lookup::Local(Resolution::Ambiguous(candidates)) => {
+            return ItemLookup {
+                candidates: candidate_docs(candidates),
+                ..ItemLookup::default()
+            }
+        }
+        Lookup::External(item) => return external_lookup(item),
+    };
+
+    let examples = examples::collect_examples(krate, candidate.item, &candidate.path)
+        .into_iter()
+        .map(|example| ExampleDoc {
+            owner: example.owner.join("::"),
+            attributes: example.attributes,
+            code: example.code,
+        })
+        .collect();
+
+    ItemLookup {
+        examples,
+        ..ItemLookup::default()
+    }
+}
+
+/// What the referring crate knows about an item of another crate
+fn external_lookup(external: &ExternalItem) -> ItemLookup {
+    let path = external.exported_as.as_ref().unwrap_or(&external.path);
+    let item = ItemDoc {
+        name: path.last().cloned().unwrap_or_default(),
+        kind: external.kind.name().to_string(),
+        path: path.join("::"),
+        reexport_of: external
+            .exported_as
+            .as_ref()
+            .map(|_| external.path.join("::")),
+        docs_url: external.docs_url.clone(),
+        visibility: None,
+        signature: None,
+        docs: None,
+        deprecation: None,
+        non_exhaustive: false,
+        struct_kind: None,
+        fields: Vec::new(),
+        has_hidden_fields: false,
+        variants: Vec::new(),
+        has_hidden_variants: false,
+        qualifiers: Vec::new(),
+        trait_items: Vec::new(),
+        implementor_count: None,
+        impls: Vec::new(),
+        references: Vec::new(),
+    };
+
+    ItemLookup {
+        item: Some(item),
+        defined_in: Some(CrateRef {
+            name: external.crate_name.clone(),
+            version: external.version.clone(),
+        }),
+        ..ItemLookup::default()
+    }
+}
+
+fn deprecation_doc(deprecation: &Deprecation) -> DeprecationDoc {
+    DeprecationDoc {
+        since: deprecation.since.clone(),
+        note: deprecation.note.clone(),
+    }
+}
+
+/// Declaration of an item that fits on one line
+fn item_signature(krate: &Crate, item: &Item) -> Option<String> {
+    let name = item.name.as_ref()?;
+    match &item.inner {
+        ItemEnum::Function(func) => Some(signature::format_function_signature(
+            name,
+            &item.visibility,
+            func,
+        )),
+        ItemEnum::AssocConst { .. } | ItemEnum::AssocType { .. } => assoc_item_declaration(item),
+        ItemEnum::Variant(variant) => Some(format_variant_declaration(krate, name, variant)),
+        ItemEnum::StructField(type_) => {
+            Some(format!("{}: {}", name, signature::format_type(type_)))
+        }
+        _ => None,
+    }
+}
+
+/// Describe a single item found under `path`
+fn item_doc(krate: &Crate, item: &Item, path: &[String]) -> ItemDoc {
+    let name = path
+        .last()
+        .or(item.name.as_ref())
+        .cloned()
+        .unwrap_or_default();
+    let kind = item.inner.kind();
+    let canonical = krate.paths.get(&item.id);
+    let docs = links::render_docs(krate, item);
+
+    let mut doc = ItemDoc {
+        name,
+        kind: kind.name().to_string(),
+        path: path.join("::"),
+        reexport_of: canonical
+            .filter(|summary| summary.path != path)
+            .map(|summary| summary.path.join("::")),
+        docs_url: canonical.and_then(|summary| {
+            links::docs_url(krate, summary.crate_id, &summary.path, summary.kind)
+        }),
+        visibility: match item.visibility {
+            Visibility::Public | Visibility::Default => None,
+            ref visibility => Some(signature::format_visibility(visibility).trim().to_string()),
+        },
+        signature: item_signature(krate, item),
+        docs: docs.as_ref().map(|docs| docs.text.clone()),
+        deprecation: item.deprecation.as_ref().map(deprecation_doc),
+        non_exhaustive: is_non_exhaustive(item),
+        struct_kind: None,
+        fields: Vec::new(),
+        has_hidden_fields: false,
+        variants: Vec::new(),
+        has_hidden_variants: false,
+        qualifiers: Vec::new(),
+        trait_items: Vec::new(),
+        implementor_count: None,
+        impls: Vec::new(),
+        references: docs
+            .map(|docs| docs.references.into_iter().map(reference_doc).collect())
+            .unwrap_or_default(),
+    };
+
+    match &item.inner {
+        ItemEnum::Struct(struct_data) => {
+            doc.struct_kind = Some(match &struct_data.kind {
+                StructKind::Plain {
+                    fields,
+                    has_stripped_fields,
+                } => {
+                    add_fields(krate, &mut doc, fields, *has_stripped_fields);
+                    StructKindDoc::Plain
+                }
+                StructKind::Tuple(fields) => {
+                    let visible: Vec<Id> = fields.iter().flatten().copied().collect();
+                    add_fields(krate, &mut doc, &visible, visible.len() < fields.len());
+                    StructKindDoc::Tuple
+                }
+                StructKind::Unit => StructKindDoc::Unit,
+            });
+            doc.impls = impl_docs(krate, &struct_data.impls);
+        }
+        ItemEnum::Union(union_data) => {
+            add_fields(
+                krate,
+                &mut doc,
+                &union_data.fields,
+                union_data.has_stripped_fields,
+            );
+            doc.impls = impl_docs(krate, &union_data.impls);
+        }
+        ItemEnum::Enum(enum_data) => {
+            doc.variants = enum_data
+                .variants
+                .iter()
+                .filter_map(|id| krate.index.get(id))
+                .filter_map(|variant_item| match &variant_item.inner {
+                    ItemEnum::Variant(variant) => {
+                        let name = variant_item.name.clone()?;
+                        let fields = match &variant.kind {
+                            VariantKind::Struct { fields, .. } => field_docs(krate, fields),
+                            _ => Vec::new(),
+                        };
+                        Some(VariantDoc {
+                            declaration: format_variant_declaration(krate, &name, variant),
+                            name,
+                            non_exhaustive: is_non_exhaustive(variant_item),
+                            summary: summary(variant_item),
+                            fields,
+                        })
+                    }
+                    _ => None,
+                })
+                .collect();
+            doc.has_hidden_variants = enum_data.has_stripped_variants;
+            doc.impls = impl_docs(krate, &enum_data.impls);
+        }
+        ItemEnum::Primitive(primitive) => doc.impls = impl_docs(krate, &primitive.impls),
+        ItemEnum::Trait(trait_data) => {
+            if trait_data.is_auto {
+                doc.qualifiers.push("auto".to_string());
+            }
+            if trait_data.is_unsafe {
+                doc.qualifiers.push("unsafe".to_string());
+            }
+            doc.trait_items = trait_item_docs(krate, trait_data);
+            doc.implementor_count = Some(trait_data.implementations.len());
+        }
+        _ => {}
+    }
+
+    doc
+}
+
+/// Add the public fields of a struct or union.
+///
+/// Fields not visible outside the crate only show up when the docs were built
+/// with private items, and are counted as hidden like stripped ones.
+fn add_fields(krate: &Crate, doc: &mut ItemDoc, field_ids: &[Id], has_stripped_fields: bool) {
+    let public: Vec<Id> = resolve_fields(krate, field_ids)
+        .filter(|(field, _)| matches!(field.visibility, Visibility::Public))
+        .map(|(field, _)| field.id)
+        .collect();
+    doc.has_hidden_fields = has_stripped_fields || public.len() < field_ids.len();
+    doc.fields = field_docs(krate, &public);
+}
+
+fn field_docs(krate: &Crate, field_ids: &[Id]) -> Vec<FieldDoc> {
+    resolve_fields(krate, field_ids)
+        .map(|(field, type_)| FieldDoc {
+            name: field.name.clone().unwrap_or_else(|| "_".to_string()),
+            type_: signature::format_type(type_),
+            summary: summary(field),
+        })
+        .collect()
+}
+
+fn assoc_item_doc(item: &Item) -> Option<AssocItemDoc> {
+    Some(AssocItemDoc {
+        name: item.name.clone()?,
+        kind: item.inner.kind().name().to_string(),
+        declaration: assoc_item_declaration(item)?,
+        provided: matches!(&item.inner, ItemEnum::Function(func) if func.has_body),
+        summary: summary(item),
+    })
+}
+
+pub(super) fn trait_item_docs(krate: &Crate, trait_data: &Trait) -> Vec<AssocItemDoc> {
+    trait_data
+        .items
+        .iter()
+        .filter_map(|id| krate.index.get(id))
+        .filter_map(assoc_item_doc)
+        .collect()
+}
+
+fn impl_doc(krate: &Crate, impl_data: &Impl) -> ImplDoc {
+    let header = signature::format_impl_header(impl_data);
+    let kind = if impl_data.is_synthetic {
+        ImplKind::Auto
+    } else if impl_data.blanket_impl.is_some() {
+        ImplKind::Blanket
+    } else if impl_data.trait_.is_none() {
+        ImplKind::Inherent
+    } else {
+        ImplKind::Trait
+    };
+    if matches!(kind, ImplKind::Auto | ImplKind::Blanket) {
+        return ImplDoc {
+            header,
+            kind,
+            items: Vec::new(),
+            provided_methods: Vec::new(),
+        };
+    }
+
+    let items = impl_data
+        .items
+        .iter()
+        .filter_map(|id| krate.index.get(id))
+        // Trait impl items have default visibility, inherent ones need `pub`
+        .filter(|item| matches!(item.visibility, Visibility::Public | Visibility::Default))
+        .filter_map(assoc_item_doc)
+        // Methods of an impl always have a body, so none of them are "provided"
+        .map(|doc| AssocItemDoc {
+            provided: false,
+            ..doc
+        })
+        .collect();
+    let mut provided_methods = impl_data.provided_trait_methods.clone();
+    provided_methods.sort();
+
+    ImplDoc {
+        header,
+        kind,
+        items,
+        provided_methods,
+    }
+}
+
+/// Describe the impl blocks of a type, in the order the Markdown lists them
+fn impl_docs(krate: &Crate, impl_ids: &[Id]) -> Vec<ImplDoc> {
+    let mut impls: Vec<ImplDoc> = impl_ids
+        .iter()
+        .filter_map(|id| match &krate.index.get(id)?.inner {
+            ItemEnum::Impl(impl_data) => Some(impl_doc(krate, impl_data)),
+            _ => None,
+        })
+        .collect();
+    impls.sort_by_key(|impl_doc| impl_doc.kind as u8);
+    impls
+}
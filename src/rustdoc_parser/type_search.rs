@@ -0,0 +1,712 @@
+//! Hoogle-style search for functions by their parameter and return types.
+//!
+//! Queries look like `&str -> Result<Url, _>` or `Vec<u8> -> String`. Both
+//! the query and every function signature are reduced to [`Shape`]s, which
+//! keep only the last path segment of named types and treat generic
+//! parameters, `_` and single-letter type names as wildcards. Candidates are
+//! ranked by how much had to be wildcarded or coerced to make them fit.
+
+use anyhow::Result;
+
+use crate::error::DocsError;
+
+use super::format::doc_summary;
+use super::model::{
+    AssocItemConstraintKind, Crate, Function, GenericArg, GenericArgs, GenericBound, Id, Item,
+    ItemEnum, Path, Term, Type, Visibility,
+};
+use super::signature;
+
+/// Cost of a query wildcard matching a concrete type
+const WILDCARD_COST: u32 = 1;
+/// Cost of a generic parameter in the signature accepting the query type
+const GENERIC_COST: u32 = 2;
+/// Cost of matching `&T` against `T` or the other way around
+const REF_MISMATCH_COST: u32 = 3;
+/// Cost of each parameter the query doesn't mention
+const EXTRA_PARAM_COST: u32 = 3;
+
+/// Simplified type used for matching
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Shape {
+    Any,
+    /// Named type or trait with its type arguments, e.g. `Result<_, Error>`
+    Named(String, Vec<Shape>),
+    Ref {
+        is_mutable: bool,
+        inner: Box<Shape>,
+    },
+    Tuple(Vec<Shape>),
+    Slice(Box<Shape>),
+}
+
+/// Parsed `inputs -> output` query
+#[derive(Debug)]
+struct TypeQuery {
+    /// `None` when the query has no arrow and only constrains the output
+    inputs: Option<Vec<Shape>>,
+    output: Option<Shape>,
+}
+
+impl TypeQuery {
+    fn parse(query: &str) -> Result<Self> {
+        let (inputs, output) = match split_arrow(query) {
+            Some((inputs, output)) => (Some(inputs), output),
+            None => (None, query),
+        };
+
+        let inputs = inputs
+            .map(|inputs| {
+                split_top_level(inputs, ',')
+                    .into_iter()
+                    .filter(|input| !input.trim().is_empty())
+                    .map(parse_shape)
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?;
+        let output = if output.trim().is_empty() {
+            None
+        } else {
+            Some(parse_shape(output)?)
+        };
+
+        if inputs.is_none() && output.is_none() {
+            return Err(DocsError::InvalidArgument("Empty type query".to_string()).into());
+        }
+        Ok(Self { inputs, output })
+    }
+}
+
+/// Split a query at its top-level `->`, outside of any brackets
+fn split_arrow(query: &str) -> Option<(&str, &str)> {
+    let mut depth = 0i32;
+    let bytes = query.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'<' | b'(' | b'[' => depth += 1,
+            b')' | b']' => depth -= 1,
+            // Don't count the `>` of an arrow as a closing bracket
+            b'>' if i == 0 || bytes[i - 1] != b'-' => depth -= 1,
+            b'-' if depth == 0 && bytes.get(i + 1) == Some(&b'>') => {
+                return Some((&query[..i], &query[i + 2..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Split on a separator that isn't nested inside brackets
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    let mut previous = None;
+    for (i, c) in text.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            // Don't count the `>` of an arrow as a closing bracket
+            '>' if previous != Some('-') => depth -= 1,
+            c if c == separator && depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+        previous = Some(c);
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Parse a type as written in a query
+fn parse_shape(text: &str) -> Result<Shape> {
+    let text = text.trim();
+    let invalid = || {
+        anyhow::Error::from(DocsError::InvalidArgument(format!(
+            "Can't parse type '{}'",
+            text
+        )))
+    };
+
+    if text.is_empty() {
+        return Err(invalid());
+    }
+    if text == "_" || text == "!" {
+        return Ok(Shape::Any);
+    }
+
+    if let Some(rest) = text.strip_prefix('&') {
+        let mut rest = rest.trim_start();
+        // Lifetimes don't take part in matching
+        if let Some(lifetime) = rest.strip_prefix('\'') {
+            rest = lifetime
+                .trim_start_matches(|c: char| c.is_alphanumeric() || c == '_')
+                .trim_start();
+        }
+        let (is_mutable, rest) = match rest.strip_prefix("mut ") {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        return Ok(Shape::Ref {
+            is_mutable,
+            inner: Box::new(parse_shape(rest)?),
+        });
+    }
+
+    if let Some(rest) = text.strip_prefix('*') {
+        let rest = rest.trim_start();
+        let (name, rest) = match rest.strip_prefix("mut ") {
+            Some(rest) => ("*mut", rest),
+            None => ("*const", rest.strip_prefix("const ").unwrap_or(rest)),
+        };
+        return Ok(Shape::Named(name.to_string(), vec![parse_shape(rest)?]));
+    }
+
+    if let Some(inner) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        // Arrays match like slices
+        let element = split_top_level(inner, ';')[0];
+        return Ok(Shape::Slice(Box::new(parse_shape(element)?)));
+    }
+
+    if let Some(inner) = text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        let mut elements = split_top_level(inner, ',')
+            .into_iter()
+            .filter(|element| !element.trim().is_empty())
+            .map(parse_shape)
+            .collect::<Result<Vec<_>>>()?;
+        // `(T)` is just `T`, unless written `(T,)`
+        if elements.len() == 1 && !inner.trim_end().ends_with(',') {
+            return Ok(elements.remove(0));
+        }
+        return Ok(Shape::Tuple(elements));
+    }
+
+    for keyword in ["impl ", "dyn "] {
+        if let Some(bounds) = text.strip_prefix(keyword) {
+            // Match on the first bound, e.g. `Iterator` in `impl Iterator + Send`
+            let first = split_top_level(bounds, '+')[0];
+            return parse_shape(first);
+        }
+    }
+
+    let (callable, _) = split_arrow(text).unwrap_or((text, ""));
+    let (path, args) = match callable
+        .trim_end()
+        .strip_suffix(')')
+        .and_then(|callable| callable.split_once('('))
+    {
+        // Closure traits like `Fn(&str) -> bool` match on their parameters,
+        // as they do in signatures
+        Some((path, params)) if !path.contains('<') => (path, params),
+        // A path with optional generic arguments
+        _ => match text.find('<') {
+            Some(start) => {
+                let args = text[start + 1..].strip_suffix('>').ok_or_else(invalid)?;
+                (&text[..start], args)
+            }
+            None => (text, ""),
+        },
+    };
+    let name = path.trim().rsplit("::").next().unwrap_or(path).trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(invalid());
+    }
+
+    // Single uppercase letters are generic parameters
+    if name.len() == 1 && name.chars().all(|c| c.is_ascii_uppercase()) {
+        return Ok(Shape::Any);
+    }
+
+    let args = split_top_level(args, ',')
+        .into_iter()
+        .map(str::trim)
+        .filter(|arg| !arg.is_empty() && !arg.starts_with('\''))
+        // Associated type constraints like `Item = u8` match on the type
+        .map(|arg| match split_top_level(arg, '=').as_slice() {
+            [_, type_] => parse_shape(type_),
+            _ => parse_shape(arg),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Shape::Named(name.to_string(), args))
+}
+
+/// Reduce a signature type to a shape, substituting `Self` for methods
+fn shape_of(type_: &Type, self_type: Option<&Type>) -> Shape {
+    match type_ {
+        Type::Generic(name) if name == "Self" => match self_type {
+            Some(self_type) => shape_of(self_type, None),
+            None => Shape::Any,
+        },
+        Type::ResolvedPath(path) => path_shape(path, self_type),
+        Type::Primitive(name) => Shape::Named(name.clone(), Vec::new()),
+        Type::BorrowedRef {
+            is_mutable, type_, ..
+        } => Shape::Ref {
+            is_mutable: *is_mutable,
+            inner: Box::new(shape_of(type_, self_type)),
+        },
+        Type::RawPointer { is_mutable, type_ } => Shape::Named(
+            if *is_mutable { "*mut" } else { "*const" }.to_string(),
+            vec![shape_of(type_, self_type)],
+        ),
+        Type::Tuple(types) => Shape::Tuple(types.iter().map(|t| shape_of(t, self_type)).collect()),
+        Type::Slice(type_) | Type::Array { type_, .. } | Type::Pat { type_ } => {
+            Shape::Slice(Box::new(shape_of(type_, self_type)))
+        }
+        Type::ImplTrait(bounds) => bounds
+            .iter()
+            .find_map(|bound| match bound {
+                GenericBound::TraitBound { trait_, .. } => Some(path_shape(trait_, self_type)),
+                _ => None,
+            })
+            .unwrap_or(Shape::Any),
+        Type::DynTrait(dyn_trait) => dyn_trait
+            .traits
+            .first()
+            .map(|poly| path_shape(&poly.trait_, self_type))
+            .unwrap_or(Shape::Any),
+        Type::Generic(_) | Type::FunctionPointer(_) | Type::Infer | Type::QualifiedPath { .. } => {
+            Shape::Any
+        }
+    }
+}
+
+fn path_shape(path: &Path, self_type: Option<&Type>) -> Shape {
+    let name = path.path.rsplit("::").next().unwrap_or(&path.path);
+    let args = match path.args.as_deref() {
+        Some(GenericArgs::AngleBracketed { args, constraints }) => args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArg::Type(type_) => Some(shape_of(type_, self_type)),
+                GenericArg::Infer => Some(Shape::Any),
+                GenericArg::Lifetime(_) | GenericArg::Const(_) => None,
+            })
+            .chain(
+                constraints
+                    .iter()
+                    .filter_map(|constraint| match &constraint.binding {
+                        AssocItemConstraintKind::Equality(Term::Type(type_)) => {
+                            Some(shape_of(type_, self_type))
+                        }
+                        _ => None,
+                    }),
+            )
+            .collect(),
+        Some(GenericArgs::Parenthesized { inputs, .. }) => {
+            inputs.iter().map(|t| shape_of(t, self_type)).collect()
+        }
+        _ => Vec::new(),
+    };
+    Shape::Named(name.to_string(), args)
+}
+
+/// Cost of making a query shape fit a signature shape, or `None` if it can't
+fn unify(query: &Shape, target: &Shape) -> Option<u32> {
+    match (query, target) {
+        (Shape::Any, Shape::Any) => Some(GENERIC_COST),
+        (Shape::Any, _) => Some(WILDCARD_COST),
+        (_, Shape::Any) => Some(GENERIC_COST),
+        (Shape::Named(q_name, q_args), Shape::Named(t_name, t_args)) => {
+            if q_name != t_name {
+                return None;
+            }
+            // `Result` in a query matches any `Result<..>`
+            if q_args.is_empty() {
+                return Some(0);
+            }
+            if q_args.len() != t_args.len() {
+                return None;
+            }
+            unify_all(q_args, t_args)
+        }
+        (
+            Shape::Ref {
+                is_mutable: q_mut,
+                inner: q_inner,
+            },
+            Shape::Ref {
+                is_mutable: t_mut,
+                inner: t_inner,
+            },
+        ) => {
+            let cost = unify(q_inner, t_inner)?;
+            Some(if q_mut == t_mut {
+                cost
+            } else {
+                cost + REF_MISMATCH_COST
+            })
+        }
+        (Shape::Ref { inner, .. }, target) => Some(unify(inner, target)? + REF_MISMATCH_COST),
+        (query, Shape::Ref { inner, .. }) => Some(unify(query, inner)? + REF_MISMATCH_COST),
+        (Shape::Tuple(q), Shape::Tuple(t)) if q.len() == t.len() => unify_all(q, t),
+        (Shape::Slice(q), Shape::Slice(t)) => unify(q, t),
+        _ => None,
+    }
+}
+
+fn unify_all(query: &[Shape], target: &[Shape]) -> Option<u32> {
+    query.iter().zip(target).map(|(q, t)| unify(q, t)).sum()
+}
+
+/// Cost of a function matching a query. Query inputs may match parameters in
+/// any order, and parameters the query doesn't mention add a small cost.
+fn match_signature(query: &TypeQuery, inputs: &[Shape], output: &Shape) -> Option<u32> {
+    let mut cost = match &query.output {
+        Some(query_output) => unify(query_output, output)?,
+        None => 0,
+    };
+
+    if let Some(query_inputs) = &query.inputs {
+        let mut unused: Vec<&Shape> = inputs.iter().collect();
+        for query_input in query_inputs {
+            let (index, input_cost) = unused
+                .iter()
+                .enumerate()
+                .filter_map(|(i, input)| Some((i, unify(query_input, input)?)))
+                .min_by_key(|&(_, input_cost)| input_cost)?;
+            unused.remove(index);
+            cost += input_cost;
+        }
+        cost += unused.len() as u32 * EXTRA_PARAM_COST;
+    }
+
+    Some(cost)
+}
+
+/// A function or method together with the path it's listed under and its `Self` type
+struct Callable<'a> {
+    item: &'a Item,
+    func: &'a Function,
+    path: Vec<String>,
+    self_type: Option<&'a Type>,
+}
+
+/// Collect free functions, inherent and trait impl methods, and trait methods
+fn callables<'a>(krate: &'a Crate) -> Vec<Callable<'a>> {
+    let mut callables = Vec::new();
+
+    let mut push = |item: &'a Item, parent: &[String], self_type: Option<&'a Type>| {
+        let (ItemEnum::Function(func), Some(name)) = (&item.inner, &item.name) else {
+            return;
+        };
+        if matches!(
+            item.visibility,
+            Visibility::Crate | Visibility::Restricted { .. }
+        ) {
+            return;
+        }
+        let mut path = parent.to_vec();
+        path.push(name.clone());
+        callables.push(Callable {
+            item,
+            func,
+            path,
+            self_type,
+        });
+    };
+
+    for (id, summary) in &krate.paths {
+        let Some(item) = krate.index.get(id) else {
+            continue;
+        };
+        let parent = &summary.path[..summary.path.len().saturating_sub(1)];
+        let children = |ids: &[Id]| -> Vec<&Item> {
+            ids.iter().filter_map(|id| krate.index.get(id)).collect()
+        };
+
+        match &item.inner {
+            ItemEnum::Function(_) => push(item, parent, None),
+            ItemEnum::Trait(trait_data) => {
+                for child in children(&trait_data.items) {
+                    push(child, &summary.path, None);
+                }
+            }
+            ItemEnum::Struct(s) => push_impls(krate, &s.impls, &summary.path, &mut push),
+            ItemEnum::Enum(e) => push_impls(krate, &e.impls, &summary.path, &mut push),
+            ItemEnum::Union(u) => push_impls(krate, &u.impls, &summary.path, &mut push),
+            ItemEnum::Primitive(p) => push_impls(krate, &p.impls, &summary.path, &mut push),
+            _ => {}
+        }
+    }
+
+    callables
+}
+
+/// Push the methods of a type's impls, leaving out auto trait and blanket impls
+fn push_impls<'a>(
+    krate: &'a Crate,
+    impl_ids: &[Id],
+    type_path: &[String],
+    push: &mut impl FnMut(&'a Item, &[String], Option<&'a Type>),
+) {
+    for id in impl_ids {
+        let Some(ItemEnum::Impl(impl_data)) = krate.index.get(id).map(|item| &item.inner) else {
+            continue;
+        };
+        if impl_data.is_synthetic || impl_data.blanket_impl.is_some() {
+            continue;
+        }
+        for item in impl_data.items.iter().filter_map(|id| krate.index.get(id)) {
+            push(item, type_path, Some(&impl_data.for_));
+        }
+    }
+}
+
+/// Find functions and methods whose signature fits a `inputs -> output` type query
+pub fn search_by_type(krate: &Crate, query: &str, limit: usize) -> Result<String> {
+    let type_query = TypeQuery::parse(query)?;
+    let unit = Shape::Tuple(Vec::new());
+
+    let mut hits: Vec<(u32, Callable)> = callables(krate)
+        .into_iter()
+        .filter_map(|callable| {
+            let inputs: Vec<Shape> = callable
+                .func
+                .sig
+                .inputs
+                .iter()
+                .map(|(_, type_)| shape_of(type_, callable.self_type))
+                .collect();
+            let output = match &callable.func.sig.output {
+                Some(output) => shape_of(output, callable.self_type),
+                None => unit.clone(),
+            };
+            let cost = match_signature(&type_query, &inputs, &output)?;
+            Some((cost, callable))
+        })
+        .collect();
+
+    hits.sort_by(|(a_cost, a), (b_cost, b)| {
+        a_cost
+            .cmp(b_cost)
+            .then_with(|| a.path.len().cmp(&b.path.len()))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    if hits.is_empty() {
+        return Ok(format!("No functions found matching '{}'", query));
+    }
+
+    let mut lines = vec![format!(
+        "# Functions matching `{}`\n\nShowing {} of {} matches, best first\n",
+        query,
+        hits.len().min(limit),
+        hits.len()
+    )];
+    for (cost, callable) in hits.iter().take(limit) {
+        let name = callable.item.name.as_deref().unwrap_or_default();
+        lines.push(format!(
+            "- `{}` (cost {})\n  `{}`{}",
+            callable.path.join("::"),
+            cost,
+            signature::format_function_signature_inline(
+                name,
+                &callable.item.visibility,
+                callable.func
+            ),
+            doc_summary(callable.item)
+        ));
+    }
+
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(name: &str, args: Vec<Shape>) -> Shape {
+        Shape::Named(name.to_string(), args)
+    }
+
+    fn reference(inner: Shape) -> Shape {
+        Shape::Ref {
+            is_mutable: false,
+            inner: Box::new(inner),
+        }
+    }
+
+    fn shape(text: &str) -> Shape {
+        parse_shape(text).unwrap()
+    }
+
+    #[test]
+    fn split_arrow_splits_at_top_level() {
+        assert_eq!(
+            split_arrow("&str -> Result<Url, _>"),
+            Some(("&str ", " Result<Url, _>"))
+        );
+        assert_eq!(split_arrow("Vec<u8>"), None);
+        assert_eq!(split_arrow("-> String"), Some(("", " String")));
+    }
+
+    #[test]
+    fn split_arrow_skips_arrows_in_closure_traits() {
+        assert_eq!(
+            split_arrow("Box<dyn Fn(u8) -> bool> -> usize"),
+            Some(("Box<dyn Fn(u8) -> bool> ", " usize"))
+        );
+        assert_eq!(
+            split_arrow("(impl Fn(&str) -> u8), u8 -> String"),
+            Some(("(impl Fn(&str) -> u8), u8 ", " String"))
+        );
+    }
+
+    #[test]
+    fn split_top_level_skips_arrows_in_closure_traits() {
+        assert_eq!(
+            split_top_level("Box<dyn Fn(u8, u16) -> u8>, bool", ','),
+            vec!["Box<dyn Fn(u8, u16) -> u8>", " bool"]
+        );
+    }
+
+    #[test]
+    fn parse_shape_keeps_last_path_segment_and_arguments() {
+        assert_eq!(
+            shape("Result<url::Url, _>"),
+            named("Result", vec![named("Url", vec![]), Shape::Any])
+        );
+        assert_eq!(shape("Vec<u8>"), named("Vec", vec![named("u8", vec![])]));
+        assert_eq!(shape("T"), Shape::Any);
+        assert_eq!(
+            shape("[u8; 4]"),
+            Shape::Slice(Box::new(named("u8", vec![])))
+        );
+        assert!(parse_shape("Vec<u8").is_err());
+        assert!(parse_shape("").is_err());
+    }
+
+    #[test]
+    fn parse_shape_tuples() {
+        assert_eq!(
+            shape("(u8, String)"),
+            Shape::Tuple(vec![named("u8", vec![]), named("String", vec![])])
+        );
+        assert_eq!(shape("(u8)"), named("u8", vec![]));
+        assert_eq!(shape("(u8,)"), Shape::Tuple(vec![named("u8", vec![])]));
+        assert_eq!(shape("()"), Shape::Tuple(vec![]));
+    }
+
+    #[test]
+    fn parse_shape_impl_trait_matches_on_first_bound() {
+        assert_eq!(
+            shape("impl Iterator<Item = u8> + Send"),
+            named("Iterator", vec![named("u8", vec![])])
+        );
+        assert_eq!(shape("dyn Error"), named("Error", vec![]));
+    }
+
+    #[test]
+    fn parse_shape_ignores_lifetimes() {
+        assert_eq!(shape("&'a str"), reference(named("str", vec![])));
+        assert_eq!(
+            shape("&'static mut [u8]"),
+            Shape::Ref {
+                is_mutable: true,
+                inner: Box::new(Shape::Slice(Box::new(named("u8", vec![])))),
+            }
+        );
+        assert_eq!(
+            shape("Cow<'a, str>"),
+            named("Cow", vec![named("str", vec![])])
+        );
+    }
+
+    #[test]
+    fn parse_shape_closure_traits() {
+        assert_eq!(
+            shape("impl Fn(&str) -> bool"),
+            named("Fn", vec![reference(named("str", vec![]))])
+        );
+        assert_eq!(
+            shape("Box<dyn FnMut(u8, u16) -> u8>"),
+            named(
+                "Box",
+                vec![named(
+                    "FnMut",
+                    vec![named("u8", vec![]), named("u16", vec![])]
+                )]
+            )
+        );
+    }
+
+    #[test]
+    fn unify_costs() {
+        let string = named("String", vec![]);
+        assert_eq!(unify(&string, &string), Some(0));
+        assert_eq!(unify(&Shape::Any, &string), Some(WILDCARD_COST));
+        assert_eq!(unify(&string, &Shape::Any), Some(GENERIC_COST));
+        assert_eq!(
+            unify(&reference(string.clone()), &string),
+            Some(REF_MISMATCH_COST)
+        );
+        assert_eq!(unify(&string, &named("str", vec![])), None);
+    }
+
+    #[test]
+    fn unify_arguments() {
+        let result = named("Result", vec![named("Url", vec![]), named("Error", vec![])]);
+        assert_eq!(unify(&named("Result", vec![]), &result), Some(0));
+        assert_eq!(
+            unify(&shape("Result<Url, _>"), &result),
+            Some(WILDCARD_COST)
+        );
+        assert_eq!(unify(&shape("Result<Url>"), &result), None);
+        assert_eq!(unify(&shape("(u8, u8)"), &shape("(u8, u8, u8)")), None);
+    }
+
+    #[test]
+    fn match_signature_ranks_by_cost() {
+        let query = TypeQuery::parse("&str -> Result<Url, _>").unwrap();
+        let output = named(
+            "Result",
+            vec![named("Url", vec![]), named("ParseError", vec![])],
+        );
+        assert_eq!(
+            match_signature(&query, &[shape("&str")], &output),
+            Some(WILDCARD_COST)
+        );
+        assert_eq!(match_signature(&query, &[shape("String")], &output), None);
+        assert_eq!(match_signature(&query, &[], &output), None);
+    }
+
+    #[test]
+    fn match_signature_inputs_in_any_order() {
+        let query = TypeQuery::parse("Vec<u8> -> String").unwrap();
+        let output = named("String", vec![]);
+        assert_eq!(
+            match_signature(&query, &[shape("Vec<u8>")], &output),
+            Some(0)
+        );
+        assert_eq!(
+            match_signature(&query, &[shape("&Config"), shape("Vec<u8>")], &output),
+            Some(EXTRA_PARAM_COST)
+        );
+
+        let query = TypeQuery::parse("u8, &str -> bool").unwrap();
+        assert_eq!(
+            match_signature(&query, &[shape("&str"), shape("u8")], &shape("bool")),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn match_signature_without_arrow_constrains_output_only() {
+        let query = TypeQuery::parse("Vec<u8>").unwrap();
+        assert!(query.inputs.is_none());
+        assert_eq!(
+            match_signature(&query, &[shape("&Path")], &shape("Vec<u8>")),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn match_signature_closure_parameters() {
+        let query = TypeQuery::parse("(impl Fn(&str) -> bool), Vec<String> -> usize").unwrap();
+        let inputs = [shape("Vec<String>"), shape("impl Fn(&str) -> bool")];
+        assert_eq!(match_signature(&query, &inputs, &shape("usize")), Some(0));
+    }
+}
@@ -0,0 +1,254 @@
+//! Best-effort parsing of rustdoc JSON in format versions we have no types for.
+//!
+//! Items are first decoded with the newest supported `rustdoc_types`, which
+//! usually still works for minor format changes. Items that fail to decode are
+//! reduced to their name, docs and kind, keeping module contents so the module
+//! tree can still be browsed.
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::model::{
+    Crate, Deprecation, ExternalCrate, Id, Item, ItemEnum, ItemKind, ItemSummary, Module,
+    Visibility,
+};
+use super::v57 as latest;
+
+/// Parse rustdoc JSON of any format version into the model
+pub fn parse_crate(json_str: &str, cancelled: &AtomicBool) -> Result<Crate> {
+    let value: Value = serde_json::from_str(json_str)?;
+    super::check_cancelled(cancelled)?;
+    let mut ids = IdMap::default();
+
+    let root = value
+        .get("root")
+        .and_then(|root| ids.get(root))
+        .ok_or_else(|| anyhow!("Rustdoc JSON has no root item"))?;
+
+    let mut decoded = 0;
+    let index: HashMap<Id, Item> = object_entries(&value, "index")
+        .filter_map(|(_, item)| {
+            let item = convert_item(item, &mut ids, &mut decoded)?;
+            Some((item.id, item))
+        })
+        .collect();
+
+    tracing::info!(
+        "Parsed {} items untyped ({} fully decoded)",
+        index.len(),
+        decoded
+    );
+
+    let paths = object_entries(&value, "paths")
+        .filter_map(|(id, summary)| {
+            let id = ids.get(&Value::String(id.clone()))?;
+            let summary = ItemSummary {
+                crate_id: summary.get("crate_id")?.as_u64()? as u32,
+                path: summary
+                    .get("path")?
+                    .as_array()?
+                    .iter()
+                    .filter_map(|segment| Some(segment.as_str()?.to_string()))
+                    .collect(),
+                kind: kind_from_name(summary.get("kind")?.as_str()?)?,
+            };
+            Some((id, summary))
+        })
+        .collect();
+
+    let external_crates = object_entries(&value, "external_crates")
+        .filter_map(|(crate_id, external)| {
+            let external_crate = ExternalCrate {
+                name: external.get("name")?.as_str()?.to_string(),
+                html_root_url: string_field(external, "html_root_url"),
+            };
+            Some((crate_id.parse().ok()?, external_crate))
+        })
+        .collect();
+
+    Ok(Crate {
+        root,
+        crate_version: string_field(&value, "crate_version"),
+        includes_private: value
+            .get("includes_private")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        index,
+        paths,
+        external_crates,
+        format_version: value
+            .get("format_version")
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as u32,
+        search_index: Default::default(),
+        item_paths: Default::default(),
+    })
+}
+
+/// Maps item ids to model ids.
+///
+/// Recent format versions use integer ids which are kept as they are, older
+/// ones use strings like `"0:1234"` which are numbered in order of appearance,
+/// counting down from `u32::MAX` so they can't collide with integer ids.
+#[derive(Default)]
+struct IdMap {
+    interned: HashMap<String, Id>,
+}
+
+impl IdMap {
+    fn get(&mut self, value: &Value) -> Option<Id> {
+        match value {
+            Value::Number(n) => Some(Id(n.as_u64()? as u32)),
+            Value::String(s) => {
+                if let Ok(n) = s.parse::<u32>() {
+                    return Some(Id(n));
+                }
+                let next = Id(u32::MAX - self.interned.len() as u32);
+                Some(*self.interned.entry(s.clone()).or_insert(next))
+            }
+            _ => None,
+        }
+    }
+
+    fn get_all(&mut self, value: Option<&Value>) -> Vec<Id> {
+        value
+            .and_then(Value::as_array)
+            .map(|ids| ids.iter().filter_map(|id| self.get(id)).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn object_entries<'a>(
+    value: &'a Value,
+    key: &str,
+) -> impl Iterator<Item = (&'a String, &'a Value)> {
+    value
+        .get(key)
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+}
+
+fn string_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key)?.as_str().map(str::to_string)
+}
+
+fn convert_item(value: &Value, ids: &mut IdMap, decoded: &mut usize) -> Option<Item> {
+    // Prefer the typed decoding, which keeps signatures, fields and impls
+    if let Ok(item) = rustdoc_types_v57::Item::deserialize(value) {
+        *decoded += 1;
+        return Some(latest::convert_item(item));
+    }
+
+    let inner = match value.get("inner")? {
+        // Unit variants like `"extern_type"`
+        Value::String(kind) => ItemEnum::Opaque(kind_from_name(kind)?),
+        Value::Object(inner) => {
+            let (kind, details) = inner.iter().next()?;
+            match kind_from_name(kind)? {
+                ItemKind::Module => ItemEnum::Module(Module {
+                    is_crate: details
+                        .get("is_crate")
+                        .and_then(Value::as_bool)
+                        .unwrap_or(false),
+                    items: ids.get_all(details.get("items")),
+                    is_stripped: details
+                        .get("is_stripped")
+                        .and_then(Value::as_bool)
+                        .unwrap_or(false),
+                }),
+                kind => ItemEnum::Opaque(kind),
+            }
+        }
+        _ => return None,
+    };
+
+    let links = value
+        .get("links")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(text, id)| Some((text.clone(), ids.get(id)?)))
+        .collect();
+
+    let attrs = value
+        .get("attrs")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|attr| match attr {
+            Value::String(s) if s.starts_with("#") => s.clone(),
+            Value::String(s) => format!("#[{}]", s),
+            Value::Object(o) => match o.get("other").and_then(Value::as_str) {
+                Some(source) => source.to_string(),
+                None => attr.to_string(),
+            },
+            other => other.to_string(),
+        })
+        .collect();
+
+    Some(Item {
+        id: ids.get(value.get("id")?)?,
+        crate_id: value.get("crate_id").and_then(Value::as_u64).unwrap_or(0) as u32,
+        name: string_field(value, "name"),
+        visibility: convert_visibility(value.get("visibility")),
+        docs: string_field(value, "docs"),
+        links,
+        attrs,
+        deprecation: value
+            .get("deprecation")
+            .filter(|d| d.is_object())
+            .map(|d| Deprecation {
+                since: string_field(d, "since"),
+                note: string_field(d, "note"),
+            }),
+        inner,
+    })
+}
+
+fn convert_visibility(value: Option<&Value>) -> Visibility {
+    match value.and_then(Value::as_str) {
+        Some("public") => Visibility::Public,
+        Some("crate") => Visibility::Crate,
+        Some(_) => Visibility::Default,
+        // Restricted visibility is an object; we don't need its details
+        None => Visibility::Crate,
+    }
+}
+
+/// Map the snake_case kind names used in rustdoc JSON, including those of
+/// older format versions, to item kinds
+fn kind_from_name(name: &str) -> Option<ItemKind> {
+    let kind = match name {
+        "module" => ItemKind::Module,
+        "extern_crate" => ItemKind::ExternCrate,
+        "use" | "import" => ItemKind::Use,
+        "struct" => ItemKind::Struct,
+        "struct_field" => ItemKind::StructField,
+        "union" => ItemKind::Union,
+        "enum" => ItemKind::Enum,
+        "variant" => ItemKind::Variant,
+        "function" | "method" => ItemKind::Function,
+        "type_alias" | "typedef" => ItemKind::TypeAlias,
+        "constant" => ItemKind::Constant,
+        "trait" => ItemKind::Trait,
+        "trait_alias" => ItemKind::TraitAlias,
+        "impl" => ItemKind::Impl,
+        "static" => ItemKind::Static,
+        "extern_type" | "foreign_type" => ItemKind::ExternType,
+        "macro" | "proc_macro" => ItemKind::Macro,
+        "proc_attribute" => ItemKind::ProcAttribute,
+        "proc_derive" => ItemKind::ProcDerive,
+        "assoc_const" => ItemKind::AssocConst,
+        "assoc_type" => ItemKind::AssocType,
+        "primitive" => ItemKind::Primitive,
+        "keyword" => ItemKind::Keyword,
+        "attribute" => ItemKind::Attribute,
+        _ => return None,
+    };
+    Some(kind)
+}
@@ -0,0 +1,5 @@
+//! Conversion from rustdoc JSON format version 54
+
+use rustdoc_types_v54 as rustdoc_types;
+
+super::convert::impl_conversion!(structured_attrs: []);
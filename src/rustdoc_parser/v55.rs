@@ -0,0 +1,5 @@
+//! Conversion from rustdoc JSON format version 55
+
+use rustdoc_types_v55 as rustdoc_types;
+
+super::convert::impl_conversion!(structured_attrs: [MacroExport => "#[macro_export]"]);
@@ -0,0 +1,8 @@
+//! Conversion from rustdoc JSON format version 56
+
+use rustdoc_types_v56 as rustdoc_types;
+
+super::convert::impl_conversion!(
+    structured_attrs: [MacroExport => "#[macro_export]"],
+    extra_kinds: [Attribute],
+);
@@ -0,0 +1,73 @@
+//! Conversion from rustdoc JSON format version 57
+
+use rustdoc_types_v57 as rustdoc_types;
+
+super::convert::impl_conversion!(
+    structured_attrs: [MacroExport => "#[macro_export]"],
+    extra_kinds: [Attribute],
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attr(json: &str) -> String {
+        let attr: rustdoc_types::Attribute = serde_json::from_str(json).unwrap();
+        convert_attrs(vec![attr]).remove(0)
+    }
+
+    #[test]
+    fn structured_attributes_render_as_source() {
+        assert_eq!(
+            attr(r#"{"repr": {"kind": "c", "align": null, "packed": null, "int": null}}"#),
+            "#[repr(C)]"
+        );
+        assert_eq!(
+            attr(r#"{"repr": {"kind": "c", "align": 4, "packed": null, "int": "u8"}}"#),
+            "#[repr(C, u8, align(4))]"
+        );
+        assert_eq!(
+            attr(r#"{"repr": {"kind": "rust", "align": null, "packed": 1, "int": null}}"#),
+            "#[repr(packed)]"
+        );
+        assert_eq!(
+            attr(r#"{"repr": {"kind": "rust", "align": null, "packed": null, "int": null}}"#),
+            "#[repr(Rust)]"
+        );
+        assert_eq!(
+            attr(
+                r#"{"repr": {"kind": "transparent", "align": null, "packed": null, "int": null}}"#
+            ),
+            "#[repr(transparent)]"
+        );
+        assert_eq!(
+            attr(r#"{"target_feature": {"enable": ["avx2", "fma"]}}"#),
+            "#[target_feature(enable = \"avx2,fma\")]"
+        );
+        assert_eq!(
+            attr(r#"{"must_use": {"reason": "use it"}}"#),
+            "#[must_use = \"use it\"]"
+        );
+        assert_eq!(attr(r#""macro_export""#), "#[macro_export]");
+        assert_eq!(attr(r##"{"other": "#[inline]"}"##), "#[inline]");
+    }
+
+    #[test]
+    fn abis_render_as_source() {
+        use rustdoc_types::Abi;
+        assert_eq!(convert_abi(Abi::Rust), None);
+        assert_eq!(convert_abi(Abi::C { unwind: false }).as_deref(), Some("C"));
+        assert_eq!(
+            convert_abi(Abi::System { unwind: true }).as_deref(),
+            Some("system-unwind")
+        );
+        assert_eq!(
+            convert_abi(Abi::SysV64 { unwind: false }).as_deref(),
+            Some("sysv64")
+        );
+        assert_eq!(
+            convert_abi(Abi::Other("efiapi".to_string())).as_deref(),
+            Some("efiapi")
+        );
+    }
+}
@@ -5,10 +5,19 @@ use serde::Deserialize;
 #[derive(Debug, Deserialize)]
 pub struct RustdocVersionInfo {
     pub format_version: u32,
+
+    /// Version of the documented crate, if rustdoc recorded one
+    #[serde(default)]
+    pub crate_version: Option<String>,
 }
 
 /// Extract the format version from raw JSON without full deserialization
 pub fn get_format_version(json_str: &str) -> anyhow::Result<u32> {
+    Ok(get_version_info(json_str)?.format_version)
+}
+
+/// Extract the format and crate versions from raw JSON without full deserialization
+pub fn get_version_info(json_str: &str) -> anyhow::Result<RustdocVersionInfo> {
     let version_info: RustdocVersionInfo = serde_json::from_str(json_str)?;
-    Ok(version_info.format_version)
+    Ok(version_info)
 }
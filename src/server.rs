@@ -1,28 +1,50 @@
-use std::future::Future;
 use std::sync::Arc;
 
+use crate::completion;
+use crate::crate_cache::{CrateCache, CrateCacheConfig};
+use crate::disk_cache::{DiskCache, DiskCacheConfig};
 use crate::docs_fetcher::DocsFetcher;
+use crate::error::DocsError;
+use crate::progress;
+use crate::prompts;
+use crate::resources;
+use crate::rustdoc_parser::{CrateDoc, ItemLookup};
+use crate::tools::output::StructuredOutput;
+use crate::tools::search_crates::CrateSearchResults;
 use crate::tools::{
-    lookup_crate, lookup_item, search_crates, search_crates::suggest_similar_crates,
+    find_implementors, list_module, lookup_crate, lookup_examples, lookup_item, search_by_type,
+    search_crates, search_crates::suggest_similar_crates, search_items,
 };
 use anyhow::Result;
 use reqwest::Client;
 use rmcp::{
-    handler::server::router::tool::ToolRouter, handler::server::tool::Parameters, model::*, tool,
-    tool_handler, tool_router, Error as McpError, ServerHandler,
+    handler::server::router::prompt::PromptRouter, handler::server::router::tool::ToolRouter,
+    handler::server::tool::cached_schema_for_type, handler::server::wrapper::Parameters, model::*,
+    prompt, prompt_handler, prompt_router, service::RequestContext, tool, tool_handler,
+    tool_router, ErrorData as McpError, RoleServer, ServerHandler,
 };
 use std::time::Duration;
 
+/// Runtime configuration for the server
+#[derive(Debug, Clone, Default)]
+pub struct ServerConfig {
+    /// On-disk cache for fetched rustdoc JSON; `None` disables it
+    pub disk_cache: Option<DiskCacheConfig>,
+    /// In-memory cache of parsed crates
+    pub crate_cache: CrateCacheConfig,
+}
+
 #[derive(Clone)]
 pub struct DocsRsServer {
     pub client: Client,
-    pub fetcher: Arc<DocsFetcher>,
+    pub crates: Arc<CrateCache>,
     tool_router: ToolRouter<Self>,
+    prompt_router: PromptRouter<Self>,
 }
 
 #[tool_router]
 impl DocsRsServer {
-    pub fn new() -> Self {
+    pub fn new(config: ServerConfig) -> Self {
         // Create shared HTTP client with optimal settings for docs.rs
         let client = Client::builder()
             .user_agent("docsrs-mcp/0.1.0")
@@ -30,16 +52,46 @@ impl DocsRsServer {
             .build()
             .expect("Failed to create HTTP client");
 
-        let fetcher = Arc::new(DocsFetcher::new(client.clone()));
+        let disk_cache = config.disk_cache.map(DiskCache::new);
+        let fetcher = Arc::new(DocsFetcher::new(client.clone(), disk_cache));
+        let crates = Arc::new(CrateCache::new(fetcher, config.crate_cache));
         Self {
             client,
-            fetcher,
+            crates,
             tool_router: Self::tool_router(),
+            prompt_router: Self::prompt_router(),
         }
     }
 
+    /// Turn a failed tool call into an error result, with the error's code in
+    /// the result metadata and similar crate names when the crate doesn't exist
+    async fn error_result(&self, crate_name: Option<&str>, error: anyhow::Error) -> CallToolResult {
+        let typed = DocsError::find(&error);
+        let code = typed.map_or("internal_error", DocsError::code);
+        let mut error_message = format!("Error ({}): {}", code, error);
+
+        if let (Some(DocsError::CrateNotFound { .. }), Some(crate_name)) = (typed, crate_name) {
+            if let Ok(suggestions) = suggest_similar_crates(&self.client, crate_name, 5).await {
+                // Only show suggestions if we found actual alternatives
+                if !suggestions.is_empty() && !suggestions.iter().any(|s| s == crate_name) {
+                    error_message.push_str("\n\nDid you mean one of these crates?\n");
+                    for suggestion in suggestions {
+                        error_message.push_str(&format!("- {}\n", suggestion));
+                    }
+                }
+            }
+        }
+
+        let mut meta = Meta::new();
+        meta.insert("errorCode".to_string(), code.into());
+        let mut result = CallToolResult::error(vec![Content::text(error_message)]);
+        result.meta = Some(meta);
+        result
+    }
+
     #[tool(
         description = "Lookup documentation for a Rust crate from docs.rs",
+        output_schema = cached_schema_for_type::<CrateDoc>(),
         annotations(
             title = "Lookup Rust Crate Documentation",
             read_only_hint = true,
@@ -51,34 +103,19 @@ impl DocsRsServer {
     async fn lookup_crate_docs(
         &self,
         Parameters(params): Parameters<lookup_crate::LookupCrateParams>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        match lookup_crate::handle(&self.fetcher, params.clone()).await {
-            Ok(content) => Ok(CallToolResult::success(vec![Content::text(content)])),
-            Err(e) => {
-                let mut error_message = format!("Error: {}", e);
-
-                // If crate not found, suggest similar crates
-                if e.to_string().contains("not found") {
-                    if let Ok(suggestions) =
-                        suggest_similar_crates(&self.client, &params.crate_name, 5).await
-                    {
-                        // Only show suggestions if we found actual alternatives
-                        if !suggestions.is_empty() && !suggestions.contains(&params.crate_name) {
-                            error_message.push_str("\n\nDid you mean one of these crates?\n");
-                            for suggestion in suggestions {
-                                error_message.push_str(&format!("- {}\n", suggestion));
-                            }
-                        }
-                    }
-                }
-
-                Ok(CallToolResult::success(vec![Content::text(error_message)]))
-            }
+        let crate_name = params.crate_name.clone();
+        let handler = lookup_crate::handle(&self.crates, params);
+        match progress::track(&context, handler).await {
+            Ok(output) => Ok(structured_result(output)),
+            Err(e) => Ok(self.error_result(Some(&crate_name), e).await),
         }
     }
 
     #[tool(
         description = "Lookup documentation for a specific item (struct, function, etc.) in a Rust crate",
+        output_schema = cached_schema_for_type::<ItemLookup>(),
         annotations(
             title = "Lookup Rust Item Documentation",
             read_only_hint = true,
@@ -90,18 +127,134 @@ impl DocsRsServer {
     async fn lookup_item_docs(
         &self,
         Parameters(params): Parameters<lookup_item::LookupItemParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = params.crate_name.clone();
+        let handler = lookup_item::handle(&self.crates, &self.client, params);
+        match progress::track(&context, handler).await {
+            Ok(output) => Ok(structured_result(output)),
+            Err(e) => Ok(self.error_result(Some(&crate_name), e).await),
+        }
+    }
+
+    #[tool(
+        description = "Get the code examples from the documentation of an item in a Rust crate, its methods and its module, with hidden lines removed",
+        annotations(
+            title = "Lookup Rust Code Examples",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = true
+        )
+    )]
+    async fn lookup_item_examples(
+        &self,
+        Parameters(params): Parameters<lookup_examples::LookupExamplesParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = params.crate_name.clone();
+        let handler = lookup_examples::handle(&self.crates, &self.client, params);
+        match progress::track(&context, handler).await {
+            Ok(content) => Ok(CallToolResult::success(vec![Content::text(content)])),
+            Err(e) => Ok(self.error_result(Some(&crate_name), e).await),
+        }
+    }
+
+    #[tool(
+        description = "Find every type in a Rust crate that implements a trait, with the trait's required and provided methods",
+        annotations(
+            title = "Find Trait Implementors",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = true
+        )
+    )]
+    async fn find_implementors(
+        &self,
+        Parameters(params): Parameters<find_implementors::FindImplementorsParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = params.crate_name.clone();
+        let handler = find_implementors::handle(&self.crates, params);
+        match progress::track(&context, handler).await {
+            Ok(content) => Ok(CallToolResult::success(vec![Content::text(content)])),
+            Err(e) => Ok(self.error_result(Some(&crate_name), e).await),
+        }
+    }
+
+    #[tool(
+        description = "List the public items of a module in a Rust crate grouped by kind, optionally including submodules, with cursor-based pagination",
+        annotations(
+            title = "List Rust Module Contents",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = true
+        )
+    )]
+    async fn list_module(
+        &self,
+        Parameters(params): Parameters<list_module::ListModuleParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = params.crate_name.clone();
+        let handler = list_module::handle(&self.crates, params);
+        match progress::track(&context, handler).await {
+            Ok(content) => Ok(CallToolResult::success(vec![Content::text(content)])),
+            Err(e) => Ok(self.error_result(Some(&crate_name), e).await),
+        }
+    }
+
+    #[tool(
+        description = "Full-text search over the items of a Rust crate by name, path and documentation, optionally filtered by item kind and module",
+        annotations(
+            title = "Search Rust Crate Items",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = true
+        )
+    )]
+    async fn search_items(
+        &self,
+        Parameters(params): Parameters<search_items::SearchItemsParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = params.crate_name.clone();
+        let handler = search_items::handle(&self.crates, params);
+        match progress::track(&context, handler).await {
+            Ok(content) => Ok(CallToolResult::success(vec![Content::text(content)])),
+            Err(e) => Ok(self.error_result(Some(&crate_name), e).await),
+        }
+    }
+
+    #[tool(
+        description = "Find functions and methods in a Rust crate by type signature, e.g. \"&str -> Result<Url, _>\", with generics treated as wildcards",
+        annotations(
+            title = "Search Rust Functions by Type",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = true
+        )
+    )]
+    async fn search_by_type(
+        &self,
+        Parameters(params): Parameters<search_by_type::SearchByTypeParams>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        match lookup_item::handle(&self.fetcher, params).await {
+        let crate_name = params.crate_name.clone();
+        let handler = search_by_type::handle(&self.crates, params);
+        match progress::track(&context, handler).await {
             Ok(content) => Ok(CallToolResult::success(vec![Content::text(content)])),
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Error: {}",
-                e
-            ))])),
+            Err(e) => Ok(self.error_result(Some(&crate_name), e).await),
         }
     }
 
     #[tool(
         description = "Search for Rust crates on crates.io with fuzzy/partial name matching",
+        output_schema = cached_schema_for_type::<CrateSearchResults>(),
         annotations(
             title = "Search Rust Crates",
             read_only_hint = true,
@@ -113,34 +266,178 @@ impl DocsRsServer {
     async fn search_crates(
         &self,
         Parameters(params): Parameters<search_crates::SearchCratesParams>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        match search_crates::handle(&self.client, params).await {
-            Ok(content) => Ok(CallToolResult::success(vec![Content::text(content)])),
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Error: {}",
-                e
-            ))])),
+        let handler = search_crates::handle(&self.client, params);
+        match progress::track(&context, handler).await {
+            Ok(output) => Ok(structured_result(output)),
+            Err(e) => Ok(self.error_result(None, e).await),
         }
     }
 }
 
+#[prompt_router]
+impl DocsRsServer {
+    #[prompt(
+        description = "Explain the core API of a crate, with its documentation overview included"
+    )]
+    async fn explain_crate(
+        &self,
+        Parameters(params): Parameters<prompts::ExplainCrateParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        let handler = prompts::explain_crate(&self.crates, params);
+        progress::track(&context, handler)
+            .await
+            .map_err(prompts::prompt_error)
+    }
+
+    #[prompt(
+        description = "Explain how to use and implement a trait, with its documentation and implementors included"
+    )]
+    async fn use_trait(
+        &self,
+        Parameters(params): Parameters<prompts::UseTraitParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        let handler = prompts::use_trait(&self.crates, &self.client, params);
+        progress::track(&context, handler)
+            .await
+            .map_err(prompts::prompt_error)
+    }
+
+    #[prompt(
+        description = "Guide a migration between two versions of a crate, with the public items added and removed and the documentation of both versions included"
+    )]
+    async fn migrate_version(
+        &self,
+        Parameters(params): Parameters<prompts::MigrateVersionParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        let handler = prompts::migrate_version(&self.crates, params);
+        progress::track(&context, handler)
+            .await
+            .map_err(prompts::prompt_error)
+    }
+
+    #[prompt(
+        description = "Recommend a crate for a task, with the crates.io search results and the documentation of the top candidates included"
+    )]
+    async fn pick_crate(
+        &self,
+        Parameters(params): Parameters<prompts::PickCrateParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        let handler = prompts::pick_crate(&self.crates, &self.client, params);
+        progress::track(&context, handler)
+            .await
+            .map_err(prompts::prompt_error)
+    }
+}
+
+/// A successful tool result carrying its structured content alongside the text.
+///
+/// Structured content is included whatever the text format, except in
+/// responses the client limited with `maxChars` or `maxTokens`, which only
+/// carry their chunk of the text.
+fn structured_result(output: StructuredOutput) -> CallToolResult {
+    let mut result = CallToolResult::success(vec![Content::text(output.text)]);
+    result.structured_content = output.structured;
+    result
+}
+
 #[tool_handler]
+#[prompt_handler]
 impl ServerHandler for DocsRsServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::LATEST,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .enable_prompts()
+                .enable_completions()
+                .build(),
             server_info: Implementation {
                 name: "docsrs-mcp".to_string(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
+                ..Default::default()
             },
             instructions: Some(
                 "MCP server for accessing Rust crate documentation via docs.rs JSON API. \
                  Use 'lookup_crate_docs' to get an overview of a crate, 'lookup_item_docs' to \
-                 find specific items like structs or functions, and 'search_crates' to search \
-                 for crates by name on crates.io."
+                 find specific items like structs or functions, 'lookup_item_examples' to get \
+                 usage examples for an item, 'list_module' to browse the \
+                 contents of a module, 'search_items' to search a \
+                 crate's items by keyword, 'search_by_type' to find functions by signature, \
+                 'find_implementors' to list the types implementing \
+                 a trait, and 'search_crates' to search for crates by name on crates.io. \
+                 Every tool accepts 'maxTokens' or 'maxChars' to cap the response size; \
+                 long sections are cut short with markers giving a 'continuation' cursor \
+                 to read on from where each was cut. \
+                 'lookup_crate_docs', 'lookup_item_docs' and 'search_crates' also return \
+                 structured content unless the response is limited, and 'outputFormat': \
+                 'json' puts it in the text too, whole, so it can't be combined with \
+                 'maxTokens' or 'maxChars'. \
+                 Crate and item documentation can also be read as resources at \
+                 'docsrs://{crate}/{version}' and 'docsrs://{crate}/{version}/{item_path}', \
+                 and prompts bundle the documentation for explaining a crate, using a trait, \
+                 migrating between versions and picking a crate."
                     .to_string(),
             ),
         }
     }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        Ok(ListResourcesResult {
+            resources: resources::recent_crates(&self.crates),
+            next_cursor: None,
+        })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, McpError> {
+        Ok(ListResourceTemplatesResult {
+            resource_templates: resources::templates(),
+            next_cursor: None,
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let handler = resources::read(&self.crates, &self.client, &request.uri);
+        progress::track(&context, handler)
+            .await
+            .map_err(|e| resources::read_error(&request.uri, e))
+    }
+
+    async fn complete(
+        &self,
+        request: CompleteRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CompleteResult, McpError> {
+        // Completion is a convenience, so failures just mean no suggestions
+        let completion = match completion::complete(&self.crates, &self.client, &request).await {
+            Ok(completion) => completion,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to complete argument '{}': {:#}",
+                    request.argument.name,
+                    e
+                );
+                CompletionInfo::default()
+            }
+        };
+        Ok(CompleteResult { completion })
+    }
 }
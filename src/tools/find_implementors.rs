@@ -0,0 +1,52 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::crate_cache::CrateCache;
+use crate::rustdoc_parser;
+use crate::tools::output::OutputLimit;
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct FindImplementorsParams {
+    /// Name of the Rust crate
+    #[serde(rename = "crateName")]
+    pub crate_name: String,
+
+    /// Path to the trait (e.g., "Service", "io::AsyncRead" or "serde::Serialize")
+    #[serde(rename = "traitPath")]
+    pub trait_path: String,
+
+    /// Specific version or semver range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// Target platform
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+
+    /// Response size limit and continuation cursor
+    #[serde(flatten)]
+    pub output: OutputLimit,
+}
+
+pub async fn handle(crates: &CrateCache, params: FindImplementorsParams) -> Result<String> {
+    tracing::info!(
+        "Finding implementors of {} in crate {} (version: {:?})",
+        params.trait_path,
+        params.crate_name,
+        params.version
+    );
+
+    // Fetch and parse the rustdoc JSON (cached across calls)
+    let krate = crates
+        .get(
+            &params.crate_name,
+            params.version.as_deref(),
+            params.target.as_deref(),
+        )
+        .await?;
+
+    let content = rustdoc_parser::find_implementors(&krate, &params.trait_path)?;
+
+    params.output.apply(content)
+}
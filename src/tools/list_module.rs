@@ -0,0 +1,82 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::crate_cache::CrateCache;
+use crate::rustdoc_parser;
+use crate::tools::output::OutputLimit;
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ListModuleParams {
+    /// Name of the Rust crate
+    #[serde(rename = "crateName")]
+    pub crate_name: String,
+
+    /// Path of the module to list (e.g., "sync" or "tokio::sync"), omit for the crate root
+    #[serde(
+        rename = "modulePath",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub module_path: Option<String>,
+
+    /// How many levels of submodules to include (default: 1, only the module itself)
+    #[serde(default = "default_depth")]
+    pub depth: usize,
+
+    /// Cursor returned by a previous call, to fetch the next page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+
+    /// Maximum number of items per page (default: 100)
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+
+    /// Specific version or semver range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// Target platform
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+
+    /// Response size limit and continuation cursor
+    #[serde(flatten)]
+    pub output: OutputLimit,
+}
+
+fn default_depth() -> usize {
+    1
+}
+
+fn default_limit() -> usize {
+    100
+}
+
+pub async fn handle(crates: &CrateCache, params: ListModuleParams) -> Result<String> {
+    tracing::info!(
+        "Listing module {:?} in crate {} (version: {:?})",
+        params.module_path,
+        params.crate_name,
+        params.version
+    );
+
+    // Fetch and parse the rustdoc JSON (cached across calls)
+    let krate = crates
+        .get(
+            &params.crate_name,
+            params.version.as_deref(),
+            params.target.as_deref(),
+        )
+        .await?;
+
+    let content = rustdoc_parser::list_module(
+        &krate,
+        params.module_path.as_deref(),
+        params.depth,
+        params.cursor.as_deref(),
+        params.limit,
+    )?;
+
+    params.output.apply(content)
+}
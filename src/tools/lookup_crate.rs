@@ -2,8 +2,9 @@ use anyhow::Result;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::docs_fetcher::DocsFetcher;
+use crate::crate_cache::CrateCache;
 use crate::rustdoc_parser;
+use crate::tools::output::{OutputFormat, OutputLimit, StructuredOutput};
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct LookupCrateParams {
@@ -18,27 +19,43 @@ pub struct LookupCrateParams {
     /// Target platform (e.g., "i686-pc-windows-msvc")
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub target: Option<String>,
+
+    /// Text format of the response: "markdown" (default), or "json" for the
+    /// structured result, which is also returned as structured content. JSON
+    /// is returned whole, so it can't be combined with an output limit, and
+    /// limited Markdown responses leave the structured content out.
+    #[serde(rename = "outputFormat", default)]
+    pub output_format: OutputFormat,
+
+    /// Response size limit and continuation cursor
+    #[serde(flatten)]
+    pub output: OutputLimit,
 }
 
-pub async fn handle(fetcher: &DocsFetcher, params: LookupCrateParams) -> Result<String> {
+pub async fn handle(crates: &CrateCache, params: LookupCrateParams) -> Result<StructuredOutput> {
     tracing::info!(
         "Looking up crate documentation for: {} (version: {:?})",
         params.crate_name,
         params.version
     );
 
-    // Fetch the rustdoc JSON from docs.rs
-    let json_str = fetcher
-        .fetch_crate_json(
+    // Fetch and parse the rustdoc JSON (cached across calls)
+    let krate = crates
+        .get(
             &params.crate_name,
             params.version.as_deref(),
             params.target.as_deref(),
-            None,
         )
         .await?;
 
-    // Parse and format the crate information
-    let content = rustdoc_parser::parse_crate_info(&json_str)?;
+    // Describe the crate, and format the description unless only JSON is wanted
+    let crate_doc = rustdoc_parser::crate_doc(&krate)?;
+    let content = match params.output_format {
+        OutputFormat::Markdown => rustdoc_parser::format_crate_doc(&crate_doc),
+        OutputFormat::Json => String::new(),
+    };
 
-    Ok(content)
+    params
+        .output
+        .apply_structured(params.output_format, content, &crate_doc)
 }
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use reqwest::Client;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::crate_cache::CrateCache;
+use crate::tools::lookup_item::{self, LookupItemParams, LookupMode};
+use crate::tools::output::{OutputFormat, OutputLimit};
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct LookupExamplesParams {
+    /// Name of the Rust crate
+    #[serde(rename = "crateName")]
+    pub crate_name: String,
+
+    /// Path to the item or module whose examples to return (e.g., "MyStruct", "module::my_function")
+    #[serde(rename = "itemPath")]
+    pub item_path: String,
+
+    /// Specific version or semver range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// Target platform
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+
+    /// Response size limit and continuation cursor
+    #[serde(flatten)]
+    pub output: OutputLimit,
+}
+
+pub async fn handle(
+    crates: &CrateCache,
+    client: &Client,
+    params: LookupExamplesParams,
+) -> Result<String> {
+    // Same as an item lookup in examples mode, including following
+    // re-exports into dependencies
+    let params = LookupItemParams {
+        crate_name: params.crate_name,
+        item_path: params.item_path,
+        mode: LookupMode::Examples,
+        version: params.version,
+        target: params.target,
+        output_format: OutputFormat::Markdown,
+        output: params.output,
+    };
+
+    Ok(lookup_item::handle(crates, client, params).await?.text)
+}
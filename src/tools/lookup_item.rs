@@ -1,9 +1,12 @@
 use anyhow::Result;
+use reqwest::Client;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::docs_fetcher::DocsFetcher;
-use crate::rustdoc_parser;
+use crate::crate_cache::CrateCache;
+use crate::rustdoc_parser::{self, Crate, CrateRef, ExternalItem, ItemLookup, Lookup};
+use crate::tools::output::{OutputFormat, OutputLimit, StructuredOutput};
+use crate::tools::search_crates::dependency_requirement;
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct LookupItemParams {
@@ -11,10 +14,15 @@ pub struct LookupItemParams {
     #[serde(rename = "crateName")]
     pub crate_name: String,
 
-    /// Path to specific item (e.g., "struct.MyStruct" or "fn.my_function")
+    /// Path to specific item (e.g., "MyStruct", "module::MyStruct::method", "struct.MyStruct" or "fn.my_function")
     #[serde(rename = "itemPath")]
     pub item_path: String,
 
+    /// What to return: "docs" (default) for the full documentation, or "examples" for just
+    /// the code examples from the docs of the item, its methods and its module
+    #[serde(default)]
+    pub mode: LookupMode,
+
     /// Specific version or semver range
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
@@ -22,28 +30,176 @@ pub struct LookupItemParams {
     /// Target platform
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub target: Option<String>,
+
+    /// Text format of the response: "markdown" (default), or "json" for the
+    /// structured result, which is also returned as structured content. JSON
+    /// is returned whole, so it can't be combined with an output limit, and
+    /// limited Markdown responses leave the structured content out.
+    #[serde(rename = "outputFormat", default)]
+    pub output_format: OutputFormat,
+
+    /// Response size limit and continuation cursor
+    #[serde(flatten)]
+    pub output: OutputLimit,
+}
+
+/// What a lookup returns for an item
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LookupMode {
+    /// Full documentation with signatures, fields and implementations
+    #[default]
+    Docs,
+    /// Only the code examples from the documentation
+    Examples,
+}
+
+/// Describe a looked up item in the given mode, and render the description as
+/// Markdown unless only JSON is wanted
+fn render_item(
+    krate: &Crate,
+    item_path: &str,
+    lookup: &Lookup,
+    params: &LookupItemParams,
+) -> Result<(String, ItemLookup)> {
+    let markdown = params.output_format == OutputFormat::Markdown;
+    match params.mode {
+        LookupMode::Docs => {
+            let result = rustdoc_parser::describe_lookup(krate, lookup);
+            let content = if markdown {
+                rustdoc_parser::format_lookup(item_path, lookup, &result)
+            } else {
+                String::new()
+            };
+            Ok((content, result))
+        }
+        LookupMode::Examples => {
+            let result = rustdoc_parser::describe_examples(krate, lookup);
+            let content = if markdown {
+                rustdoc_parser::format_examples(item_path, lookup, &result)?
+            } else {
+                String::new()
+            };
+            Ok((content, result))
+        }
+    }
 }
 
-pub async fn handle(fetcher: &DocsFetcher, params: LookupItemParams) -> Result<String> {
+pub async fn handle(
+    crates: &CrateCache,
+    client: &Client,
+    params: LookupItemParams,
+) -> Result<StructuredOutput> {
     tracing::info!(
-        "Looking up item documentation for: {} in crate {} (version: {:?})",
+        "Looking up item {:?} for: {} in crate {} (version: {:?})",
+        params.mode,
         params.item_path,
         params.crate_name,
         params.version
     );
 
-    // Fetch the rustdoc JSON from docs.rs
-    let json_str = fetcher
-        .fetch_crate_json(
+    // Fetch and parse the rustdoc JSON (cached across calls)
+    let krate = crates
+        .get(
             &params.crate_name,
             params.version.as_deref(),
             params.target.as_deref(),
-            None, // format_version not needed for item lookup
         )
         .await?;
 
-    // Find and format the specific item
-    let content = rustdoc_parser::find_item(&json_str, &params.item_path)?;
+    let lookup = rustdoc_parser::lookup_item(&krate, &params.item_path)?;
+
+    // Items defined in a dependency are documented in that crate's JSON
+    if let Lookup::External(external) = &lookup {
+        if !external.is_std() {
+            match lookup_external(crates, client, &params, &krate, external).await {
+                Ok((content, lookup)) => {
+                    return params
+                        .output
+                        .apply_structured(params.output_format, content, &lookup)
+                }
+                Err(e) => tracing::warn!(
+                    "Failed to look up {} in {}: {:#}",
+                    external.path.join("::"),
+                    external.crate_name,
+                    e
+                ),
+            }
+        }
+    }
+
+    let (content, result) = render_item(&krate, &params.item_path, &lookup, &params)?;
+
+    params
+        .output
+        .apply_structured(params.output_format, content, &result)
+}
+
+/// Look up an item in the dependency that defines it, at the version the
+/// crate was documented against or else the version it depends on
+async fn lookup_external(
+    crates: &CrateCache,
+    client: &Client,
+    params: &LookupItemParams,
+    krate: &Crate,
+    external: &ExternalItem,
+) -> Result<(String, ItemLookup)> {
+    let version = match (&external.version, &krate.crate_version) {
+        (Some(version), _) => Some(version.clone()),
+        (None, Some(crate_version)) => {
+            dependency_requirement(
+                client,
+                &params.crate_name,
+                crate_version,
+                &external.crate_name,
+            )
+            .await?
+        }
+        (None, None) => None,
+    };
 
-    Ok(content)
+    tracing::info!(
+        "Following {} into crate {} (version: {:?})",
+        params.item_path,
+        external.crate_name,
+        version
+    );
+
+    let dependency = crates
+        .get(
+            &external.crate_name,
+            version.as_deref(),
+            params.target.as_deref(),
+        )
+        .await?;
+
+    // The item may have moved if the version didn't match exactly, so fall
+    // back to looking it up by name
+    let path = external.path.join("::");
+    let resolved = rustdoc_parser::lookup_item(&dependency, &path).or_else(|e| {
+        let name = external.path.last().ok_or(e)?;
+        rustdoc_parser::lookup_item(&dependency, name)
+    })?;
+    let (content, mut lookup) = render_item(&dependency, &path, &resolved, params)?;
+
+    let exported_as = external
+        .exported_as
+        .as_ref()
+        .map(|path| format!(" (re-exported as `{}`)", path.join("::")))
+        .unwrap_or_default();
+    let version = dependency
+        .crate_version
+        .as_ref()
+        .map(|version| format!(" v{}", version))
+        .unwrap_or_default();
+    lookup.defined_in = Some(CrateRef {
+        name: external.crate_name.clone(),
+        version: dependency.crate_version.clone(),
+    });
+
+    let content = format!(
+        "> `{}`{} is defined in the `{}`{} crate, showing its documentation from there.\n\n{}",
+        path, exported_as, external.crate_name, version, content
+    );
+    Ok((content, lookup))
 }
@@ -1,3 +1,9 @@
+pub mod find_implementors;
+pub mod list_module;
 pub mod lookup_crate;
+pub mod lookup_examples;
 pub mod lookup_item;
+pub mod output;
+pub mod search_by_type;
 pub mod search_crates;
+pub mod search_items;
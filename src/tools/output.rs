@@ -0,0 +1,607 @@
+use std::ops::Range;
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::DocsError;
+
+/// Rough number of characters per token, for turning a token budget into a length
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Smallest chunk worth returning; limits that leave less room than this next
+/// to the continuation markers are rejected rather than raised
+const MIN_CHUNK_CHARS: usize = 200;
+
+/// Smallest part of a section worth showing when a response is cut down
+/// section by section; below this, trailing sections are left out instead
+const MIN_SECTION_CHARS: usize = 100;
+
+/// Size limit on a tool's response, shared by all tools. Responses over the
+/// limit are split into chunks, each ending with a cursor for the next one.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct OutputLimit {
+    /// Maximum length of the response in characters (default: unlimited)
+    #[serde(rename = "maxChars", default, skip_serializing_if = "Option::is_none")]
+    pub max_chars: Option<usize>,
+
+    /// Maximum length of the response in tokens, estimated at 4 characters per token
+    /// (default: unlimited)
+    #[serde(rename = "maxTokens", default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+
+    /// Continuation cursor from a previous truncated response, to get its next chunk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub continuation: Option<String>,
+}
+
+/// How a tool renders its result as text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Formatted documentation, for reading
+    #[default]
+    Markdown,
+    /// The structured result serialized as JSON, for tooling
+    Json,
+}
+
+/// A tool response along with the structured result it was rendered from,
+/// for tools that publish an output schema. The structured result is left out
+/// of responses cut down to a size limit.
+#[derive(Debug, Clone)]
+pub struct StructuredOutput {
+    pub text: String,
+    pub structured: Option<Value>,
+}
+
+impl OutputLimit {
+    /// The character budget, taking the smaller of the two limits
+    fn budget(&self) -> Option<usize> {
+        let from_tokens = self
+            .max_tokens
+            .map(|tokens| tokens.saturating_mul(CHARS_PER_TOKEN));
+        match (self.max_chars, from_tokens) {
+            (Some(chars), Some(tokens)) => Some(chars.min(tokens)),
+            (chars, tokens) => chars.or(tokens),
+        }
+    }
+
+    /// Whether any limit or continuation cursor was given
+    fn is_limited(&self) -> bool {
+        self.max_chars.is_some() || self.max_tokens.is_some() || self.continuation.is_some()
+    }
+
+    /// Cut a response down to the chunk selected by the continuation cursor.
+    ///
+    /// Lengths, limits and cursors all count characters. When a whole response
+    /// doesn't fit, its first chunk keeps the top of the response (the
+    /// signature and summary) and the start of every `## ` section, cutting
+    /// long sections short with a marker giving the cursor to read on from
+    /// where each was cut. Sections that can't be given a useful share are left
+    /// out, with a marker saying where they start. Chunks fetched with a cursor
+    /// pick up from it and run on linearly through the rest of the response.
+    ///
+    /// Chunks end at section, paragraph or line boundaries where possible, code
+    /// blocks cut in half are closed with their own fence and reopened in the
+    /// next chunk, and every chunk but the last ends with a marker saying how
+    /// much is left and which cursor fetches it. Markers count towards the
+    /// limit, so no chunk is longer than it.
+    pub fn apply(&self, content: String) -> Result<String> {
+        let total = char_len(&content);
+        let start = match &self.continuation {
+            Some(cursor) => cursor
+                .parse::<usize>()
+                .ok()
+                .filter(|&start| start < total)
+                .map(|start| byte_index(&content, start))
+                .ok_or_else(|| {
+                    DocsError::InvalidArgument(format!("Invalid continuation cursor '{}'", cursor))
+                })?,
+            None => 0,
+        };
+        let Some(budget) = self.budget() else {
+            return Ok(content[start..].to_string());
+        };
+
+        let mut out = String::new();
+        if start > 0 {
+            out.push_str(&format!(
+                "[Continued from character {}]\n",
+                char_len(&content[..start])
+            ));
+            let starts_section = content[start..].trim_start().starts_with('#');
+            if let Some(heading) = last_heading(&content[..start]).filter(|_| !starts_section) {
+                out.push_str(&format!("{} (continued)\n", heading));
+            }
+            if let Some(fence) = open_fence(&content[..start]) {
+                out.push_str(fence);
+                out.push('\n');
+            }
+        }
+
+        let rest = &content[start..];
+        if char_len(&out) + char_len(rest) <= budget {
+            out.push_str(rest);
+            return Ok(out);
+        }
+        if start == 0 {
+            if let Some(chunk) = by_section(&content, budget) {
+                return Ok(chunk);
+            }
+        }
+
+        // Leave room for the markers, taking the end of the chunk to be as
+        // long as the response's length when it comes to counting digits
+        let overhead = char_len(&out)
+            + fence_close_len(&content)
+            + char_len(&truncation_marker(total, total, total));
+        let available = budget
+            .checked_sub(overhead)
+            .filter(|&available| available >= MIN_CHUNK_CHARS)
+            .ok_or_else(|| {
+                DocsError::InvalidArgument(format!(
+                    "Output limit of {} characters is too small to split this response into chunks, \
+                     allow at least {}",
+                    budget,
+                    overhead + MIN_CHUNK_CHARS
+                ))
+            })?;
+
+        let end = cut(&content, start, available);
+        out.push_str(content[start..end].trim_end());
+        close_fence(&mut out, &content[..end]);
+        out.push_str(&truncation_marker(
+            char_len(&content[..start]),
+            char_len(&content[..end]),
+            total,
+        ));
+
+        Ok(out)
+    }
+
+    /// Build the response for a result that is available both as Markdown and
+    /// as structured data. A cut-off document wouldn't match the tool's output
+    /// schema, so a size limit is only accepted along with Markdown text, and
+    /// the structured result is left out of limited responses instead of
+    /// going over the limit.
+    pub fn apply_structured<T: Serialize>(
+        &self,
+        format: OutputFormat,
+        markdown: String,
+        result: &T,
+    ) -> Result<StructuredOutput> {
+        if format == OutputFormat::Json && self.is_limited() {
+            return Err(DocsError::InvalidArgument(
+                "maxChars, maxTokens and continuation only apply to Markdown output; JSON \
+                 output is always returned whole"
+                    .to_string(),
+            )
+            .into());
+        }
+
+        if self.is_limited() {
+            return Ok(StructuredOutput {
+                text: self.apply(markdown)?,
+                structured: None,
+            });
+        }
+
+        let structured = serde_json::to_value(result)?;
+        let text = match format {
+            OutputFormat::Markdown => markdown,
+            OutputFormat::Json => serde_json::to_string_pretty(&structured)?,
+        };
+        Ok(StructuredOutput {
+            text,
+            structured: Some(structured),
+        })
+    }
+}
+
+/// Marker ending a chunk that has more after it
+fn truncation_marker(start: usize, end: usize, total: usize) -> String {
+    format!(
+        "\n\n[Truncated: showing characters {}-{} of {}. Pass continuation \"{}\" to get the next chunk.]",
+        start, end, total, end
+    )
+}
+
+/// Marker ending a section cut short in a response cut down section by section
+fn section_marker(start: usize, end: usize, total: usize) -> String {
+    format!(
+        "\n\n[Section truncated: showing characters {}-{} of {}. Pass continuation \"{}\" to read on from there.]\n\n",
+        start, end, total, end
+    )
+}
+
+/// Marker ending a response whose trailing sections were left out
+fn omitted_marker(count: usize, start: usize, total: usize) -> String {
+    format!(
+        "\n\n[Truncated: {} more sections from character {} of {}. Pass continuation \"{}\" to read on from there.]",
+        count, start, total, start
+    )
+}
+
+/// First chunk of a response that is too long, keeping the top of every
+/// section. The part before the first section gets up to half of the budget,
+/// and the sections share the rest evenly, with short sections handing what
+/// they don't use on to the longer ones. Returns `None` when the response has
+/// no sections or the budget can't fit a useful part of each.
+fn by_section(content: &str, budget: usize) -> Option<String> {
+    let pieces = sections(content);
+    if pieces.len() < 2 {
+        return None;
+    }
+
+    let total = char_len(content);
+    let overhead = fence_close_len(content) + char_len(&section_marker(total, total, total));
+    let lens: Vec<usize> = pieces
+        .iter()
+        .map(|piece| char_len(&content[piece.clone()]))
+        .collect();
+    let preamble = lens[0].min(budget / 2);
+
+    (2..=pieces.len()).rev().find_map(|shown| {
+        let omitted = match pieces.get(shown) {
+            Some(next) => omitted_marker(
+                pieces.len() - shown,
+                char_len(&content[..next.start]),
+                total,
+            ),
+            None => String::new(),
+        };
+        let room = budget.checked_sub(preamble + char_len(&omitted))?;
+
+        let mut shares = vec![preamble];
+        shares.extend(water_fill(&lens[1..shown], room));
+        let too_small = shares
+            .iter()
+            .zip(&lens)
+            .any(|(&share, &len)| share < len && share < overhead + MIN_SECTION_CHARS);
+        if too_small {
+            return None;
+        }
+
+        let mut out = String::new();
+        for (piece, (&share, &len)) in pieces.iter().zip(shares.iter().zip(&lens)) {
+            let text = &content[piece.clone()];
+            if share >= len {
+                out.push_str(text);
+                continue;
+            }
+            let end = cut(content, piece.start, share - overhead);
+            out.push_str(content[piece.start..end].trim_end());
+            close_fence(&mut out, &content[..end]);
+            out.push_str(&section_marker(
+                char_len(&content[..piece.start]),
+                char_len(&content[..end]),
+                total,
+            ));
+        }
+        if !omitted.is_empty() {
+            out.truncate(out.trim_end().len());
+            out.push_str(&omitted);
+        }
+
+        (char_len(&out) <= budget).then_some(out)
+    })
+}
+
+/// Splits `room` between parts of the given lengths, giving each an even
+/// share and handing what short parts don't need on to the longer ones
+fn water_fill(lens: &[usize], mut room: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..lens.len()).collect();
+    order.sort_by_key(|&i| lens[i]);
+
+    let mut shares = vec![0; lens.len()];
+    for (filled, &i) in order.iter().enumerate() {
+        shares[i] = lens[i].min(room / (lens.len() - filled));
+        room -= shares[i];
+    }
+    shares
+}
+
+/// Byte ranges of the part before the first `## ` heading and of each
+/// section starting at one, skipping headings inside code blocks
+fn sections(content: &str) -> Vec<Range<usize>> {
+    let mut starts = vec![0];
+    let mut fences = Fences::default();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if !fences.line(line) && line.starts_with("## ") && offset > 0 {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+
+    let ends = starts.iter().skip(1).copied().chain([content.len()]);
+    starts
+        .iter()
+        .zip(ends)
+        .map(|(&start, end)| start..end)
+        .collect()
+}
+
+/// End of the chunk starting at `start` that fits in `budget` characters,
+/// moved back so the chunk doesn't end on the opening line of a code block
+fn cut(content: &str, start: usize, budget: usize) -> usize {
+    let end = start + chunk_len(&content[start..], budget);
+    if let Some(fence) = open_fence(&content[..end]) {
+        let chunk = content[start..end].trim_end();
+        if chunk.ends_with(fence) && chunk.len() > fence.len() {
+            return start + chunk.len() - fence.len();
+        }
+    }
+    end
+}
+
+/// Length in bytes of the next chunk of `text`, preferring to end before a
+/// heading, then at a blank line, then at a line break, as long as that keeps
+/// at least half of the budget of `budget` characters
+fn chunk_len(text: &str, budget: usize) -> usize {
+    let limit = byte_index(text, budget);
+    if limit == text.len() {
+        return limit;
+    }
+
+    let window = &text[..limit];
+    let min = byte_index(text, budget / 2);
+    ["\n## ", "\n### ", "\n\n", "\n"]
+        .into_iter()
+        .find_map(|boundary| window.rfind(boundary).filter(|&pos| pos >= min))
+        .unwrap_or(limit)
+}
+
+/// Closes the code block left open at the end of `text`, if any, with the
+/// same fence that opened it
+fn close_fence(out: &mut String, text: &str) {
+    if let Some(run) = open_fence(text).and_then(fence_run) {
+        out.push('\n');
+        out.push_str(run);
+    }
+}
+
+/// Room to leave for closing a code block cut in half, as long as the
+/// longest fence in the response
+fn fence_close_len(content: &str) -> usize {
+    content
+        .lines()
+        .filter_map(fence_run)
+        .map(|run| run.len() + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// The last Markdown heading before a cut, repeated at the top of the next chunk
+fn last_heading(text: &str) -> Option<&str> {
+    let mut fences = Fences::default();
+    text.lines()
+        .filter(|line| !fences.line(line) && line.starts_with('#'))
+        .last()
+}
+
+/// The opening line of a code block left unclosed at the end of `text`
+fn open_fence(text: &str) -> Option<&str> {
+    let mut fences = Fences::default();
+    text.lines().for_each(|line| {
+        fences.line(line);
+    });
+    fences.open
+}
+
+/// Tracks fenced code blocks line by line. A block is closed by a fence of
+/// the same character at least as long as the one that opened it, with
+/// nothing after it.
+#[derive(Default)]
+struct Fences<'a> {
+    open: Option<&'a str>,
+}
+
+impl<'a> Fences<'a> {
+    /// Takes the next line, returning whether it belongs to a code block,
+    /// counting the fences themselves
+    fn line(&mut self, line: &'a str) -> bool {
+        match (self.open.and_then(fence_run), fence_run(line)) {
+            (Some(open), Some(run)) => {
+                if run.as_bytes()[0] == open.as_bytes()[0]
+                    && run.len() >= open.len()
+                    && line.trim() == run
+                {
+                    self.open = None;
+                }
+                true
+            }
+            (Some(_), None) => true,
+            (None, Some(_)) => {
+                self.open = Some(line.trim_start());
+                true
+            }
+            (None, None) => false,
+        }
+    }
+}
+
+/// The run of backticks or tildes starting a fence line
+fn fence_run(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let len = trimmed.len() - trimmed.trim_start_matches(marker).len();
+    (len >= 3).then(|| &trimmed[..len])
+}
+
+fn char_len(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Byte offset of the character at `chars`, or the end of `text`
+fn byte_index(text: &str, chars: usize) -> usize {
+    text.char_indices()
+        .nth(chars)
+        .map_or(text.len(), |(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(max_chars: usize, continuation: Option<usize>) -> OutputLimit {
+        OutputLimit {
+            max_chars: Some(max_chars),
+            max_tokens: None,
+            continuation: continuation.map(|cursor| cursor.to_string()),
+        }
+    }
+
+    /// Cursors given by the markers in a chunk, in order
+    fn cursors(chunk: &str) -> Vec<usize> {
+        chunk
+            .split("Pass continuation \"")
+            .skip(1)
+            .map(|rest| rest[..rest.find('"').unwrap()].parse().unwrap())
+            .collect()
+    }
+
+    fn paragraphs(count: usize, text: &str) -> String {
+        (0..count)
+            .map(|i| format!("Paragraph {} {}", i, text.repeat(8)))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    #[test]
+    fn unlimited_output_is_returned_whole() {
+        let content = paragraphs(50, "text ");
+        assert_eq!(
+            OutputLimit::default().apply(content.clone()).unwrap(),
+            content
+        );
+        assert_eq!(limit(10_000, None).apply(content.clone()).unwrap(), content);
+    }
+
+    #[test]
+    fn chunks_end_at_paragraphs_and_cursors_walk_the_whole_response() {
+        let content = paragraphs(20, "text ");
+        let total = char_len(&content);
+        let mut cursor = None;
+        let mut seen = 0;
+        loop {
+            let chunk = limit(400, cursor).apply(content.clone()).unwrap();
+            assert!(char_len(&chunk) <= 400, "chunk over the limit: {}", chunk);
+            let Some(&next) = cursors(&chunk).last() else {
+                assert!(chunk.ends_with(&content[byte_index(&content, seen)..]));
+                break;
+            };
+            let shown = &content[byte_index(&content, seen)..byte_index(&content, next)];
+            assert!(chunk.contains(shown.trim_end()));
+            assert!(content[byte_index(&content, next)..]
+                .trim_start()
+                .starts_with("Paragraph"));
+            assert!(next > seen && next < total);
+            seen = next;
+            cursor = Some(next);
+        }
+    }
+
+    #[test]
+    fn limits_and_cursors_count_characters() {
+        let content = paragraphs(20, "ünïcödé ");
+        let chunk = limit(400, None).apply(content.clone()).unwrap();
+        assert!(char_len(&chunk) <= 400);
+        assert!(chunk.len() > 400);
+
+        let next = cursors(&chunk)[0];
+        let chunk = limit(400, Some(next)).apply(content.clone()).unwrap();
+        let resumed = &content[byte_index(&content, next)..];
+        assert!(chunk.starts_with(&format!("[Continued from character {}]\n", next)));
+        assert!(chunk.contains(&resumed[..byte_index(resumed, 50)]));
+    }
+
+    #[test]
+    fn invalid_cursors_are_rejected() {
+        let content = paragraphs(20, "text ");
+        let past_end = char_len(&content);
+        assert!(limit(400, Some(past_end)).apply(content.clone()).is_err());
+        let mut bad = limit(400, None);
+        bad.continuation = Some("abc".to_string());
+        assert!(bad.apply(content.clone()).is_err());
+        assert!(limit(50, None).apply(content).is_err());
+    }
+
+    #[test]
+    fn code_blocks_are_closed_and_reopened_with_their_own_fence() {
+        let code = (0..40)
+            .map(|i| format!("let x{} = {};\n```\nnot a fence end\n", i, i))
+            .collect::<String>();
+        let content = format!("Intro\n\n````rust\n{}````\n\nAfter", code);
+
+        let chunk = limit(400, None).apply(content.clone()).unwrap();
+        let body = &chunk[..chunk.find("\n\n[Truncated").unwrap()];
+        assert!(body.ends_with("\n````"), "not closed: {}", body);
+
+        let next = cursors(&chunk)[0];
+        let chunk = limit(400, Some(next)).apply(content).unwrap();
+        assert!(
+            chunk.lines().nth(1) == Some("````rust"),
+            "not reopened: {}",
+            chunk
+        );
+    }
+
+    #[test]
+    fn fences_close_only_on_a_long_enough_run_of_the_same_character() {
+        assert_eq!(open_fence("````rust\n```\n"), Some("````rust"));
+        assert_eq!(open_fence("````rust\n~~~~\n"), Some("````rust"));
+        assert_eq!(open_fence("````rust\n```` x\n"), Some("````rust"));
+        assert_eq!(open_fence("````rust\n`````\n"), None);
+        assert_eq!(open_fence("~~~\n  ~~~  \n"), None);
+        assert_eq!(open_fence("``not a fence\n"), None);
+        assert_eq!(last_heading("# Top\n```\n# comment\n```\n"), Some("# Top"));
+    }
+
+    #[test]
+    fn long_responses_keep_the_start_of_every_section() {
+        let content = format!(
+            "# Item\n\n```rust\npub fn item()\n```\n\n## Documentation\n{}\n\n## Fields\n- `a`\n\n## Implementations\n{}\n",
+            paragraphs(10, "docs "),
+            paragraphs(10, "impls "),
+        );
+        let chunk = limit(1000, None).apply(content.clone()).unwrap();
+        assert!(char_len(&chunk) <= 1000, "chunk over the limit: {}", chunk);
+        assert!(chunk.starts_with("# Item\n\n```rust\npub fn item()\n```\n"));
+        assert!(chunk.contains("## Documentation\nParagraph 0"));
+        assert!(chunk.contains("## Fields\n- `a`\n\n## Implementations\nParagraph 0"));
+        assert_eq!(chunk.matches("[Section truncated").count(), 2);
+
+        // Each cursor reads on from where its section was cut
+        for cursor in cursors(&chunk) {
+            let rest = limit(1000, Some(cursor)).apply(content.clone()).unwrap();
+            let resumed = &content[byte_index(&content, cursor)..];
+            assert!(rest.contains(resumed[..byte_index(resumed, 40)].trim()));
+        }
+    }
+
+    #[test]
+    fn sections_that_cannot_fit_are_left_out() {
+        let content = (0..30)
+            .map(|i| format!("## Section {}\n{}\n", i, paragraphs(2, "words ")))
+            .collect::<String>();
+        let content = format!("# Top\n\n{}", content);
+        let chunk = limit(1200, None).apply(content.clone()).unwrap();
+        assert!(char_len(&chunk) <= 1200, "chunk over the limit: {}", chunk);
+        assert!(chunk.contains("## Section 0\n"));
+        assert!(!chunk.contains("## Section 29\n"));
+
+        let omitted = *cursors(&chunk).last().unwrap();
+        assert!(chunk.contains("more sections from character"));
+        assert!(content[byte_index(&content, omitted)..].starts_with("## Section"));
+    }
+
+    #[test]
+    fn water_fill_hands_unused_room_to_longer_parts() {
+        assert_eq!(water_fill(&[10, 500, 500], 310), vec![10, 150, 150]);
+        assert_eq!(water_fill(&[10, 20], 100), vec![10, 20]);
+        assert_eq!(water_fill(&[], 100), Vec::<usize>::new());
+    }
+}
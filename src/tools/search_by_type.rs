@@ -0,0 +1,61 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::crate_cache::CrateCache;
+use crate::rustdoc_parser;
+use crate::tools::output::OutputLimit;
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SearchByTypeParams {
+    /// Name of the Rust crate
+    #[serde(rename = "crateName")]
+    pub crate_name: String,
+
+    /// Type signature to search for, as "inputs -> output" (e.g., "&str -> Result<Url, _>" or
+    /// "Vec<u8> -> String"). Use "_" or single-letter names like "T" as wildcards; without an
+    /// arrow only the return type is matched
+    pub query: String,
+
+    /// Maximum number of results to return (default: 20)
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+
+    /// Specific version or semver range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// Target platform
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+
+    /// Response size limit and continuation cursor
+    #[serde(flatten)]
+    pub output: OutputLimit,
+}
+
+fn default_limit() -> usize {
+    20
+}
+
+pub async fn handle(crates: &CrateCache, params: SearchByTypeParams) -> Result<String> {
+    tracing::info!(
+        "Searching functions by type '{}' in crate {} (version: {:?})",
+        params.query,
+        params.crate_name,
+        params.version
+    );
+
+    // Fetch and parse the rustdoc JSON (cached across calls)
+    let krate = crates
+        .get(
+            &params.crate_name,
+            params.version.as_deref(),
+            params.target.as_deref(),
+        )
+        .await?;
+
+    let content = rustdoc_parser::search_by_type(&krate, &params.query, params.limit)?;
+
+    params.output.apply(content)
+}
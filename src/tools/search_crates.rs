@@ -3,6 +3,9 @@ use reqwest::Client;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::error::DocsError;
+use crate::tools::output::{OutputFormat, OutputLimit, StructuredOutput};
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct SearchCratesParams {
     /// Search query for crate names (supports partial matches)
@@ -11,6 +14,17 @@ pub struct SearchCratesParams {
     /// Maximum number of results to return (default: 10)
     #[serde(default = "default_limit")]
     pub limit: usize,
+
+    /// Text format of the response: "markdown" (default), or "json" for the
+    /// structured result, which is also returned as structured content. JSON
+    /// is returned whole, so it can't be combined with an output limit, and
+    /// limited Markdown responses leave the structured content out.
+    #[serde(rename = "outputFormat", default)]
+    pub output_format: OutputFormat,
+
+    /// Response size limit and continuation cursor
+    #[serde(flatten)]
+    pub output: OutputLimit,
 }
 
 fn default_limit() -> usize {
@@ -25,7 +39,6 @@ struct CratesIoSearchResponse {
 }
 
 #[derive(Debug, Deserialize)]
-#[allow(dead_code)]
 struct CrateInfo {
     name: String,
     description: Option<String>,
@@ -42,18 +55,70 @@ struct Meta {
     total: usize,
 }
 
-pub async fn handle(client: &Client, params: SearchCratesParams) -> Result<String> {
+/// Crates matching a search, most relevant first
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CrateSearchResults {
+    /// Total number of matching crates on crates.io
+    pub total: usize,
+    pub crates: Vec<CrateSearchHit>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CrateSearchHit {
+    pub name: String,
+    /// Latest version
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub downloads: u64,
+    /// Downloads in the last 90 days
+    pub recent_downloads: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+}
+
+impl From<CrateInfo> for CrateSearchHit {
+    fn from(info: CrateInfo) -> Self {
+        Self {
+            name: info.name,
+            version: info.max_version,
+            description: info.description,
+            downloads: info.downloads,
+            recent_downloads: info.recent_downloads,
+            documentation: info.documentation,
+            repository: info.repository,
+            homepage: info.homepage,
+        }
+    }
+}
+
+pub async fn handle(client: &Client, params: SearchCratesParams) -> Result<StructuredOutput> {
     tracing::info!(
         "Searching crates.io for: '{}' (limit: {})",
         params.query,
         params.limit
     );
 
+    let results = search(client, &params.query, params.limit).await?;
+    let content = format_results(&params.query, &results);
+
+    params
+        .output
+        .apply_structured(params.output_format, content, &results)
+}
+
+/// Search crates.io for crates matching a query
+pub async fn search(client: &Client, query: &str, limit: usize) -> Result<CrateSearchResults> {
     // Build the search URL
     let search_url = format!(
         "https://crates.io/api/v1/crates?q={}&per_page={}",
-        urlencoding::encode(&params.query),
-        params.limit
+        urlencoding::encode(query),
+        limit
     );
 
     // Make the request
@@ -61,14 +126,10 @@ pub async fn handle(client: &Client, params: SearchCratesParams) -> Result<Strin
         .get(&search_url)
         .send()
         .await
-        .context("Failed to send request to crates.io")?;
+        .map_err(|e| DocsError::network("crates.io", &e))?;
 
     if !response.status().is_success() {
-        return Err(anyhow::anyhow!(
-            "Failed to search crates: HTTP {} {}",
-            response.status().as_u16(),
-            response.status().canonical_reason().unwrap_or("Unknown")
-        ));
+        return Err(DocsError::upstream_status("crates.io", response.status()).into());
     }
 
     // Parse the response
@@ -77,24 +138,31 @@ pub async fn handle(client: &Client, params: SearchCratesParams) -> Result<Strin
         .await
         .context("Failed to parse crates.io response")?;
 
-    // Format the results
-    if data.crates.is_empty() {
-        return Ok(format!("No crates found matching \"{}\"", params.query));
+    Ok(CrateSearchResults {
+        total: data.meta.total,
+        crates: data.crates.into_iter().map(CrateSearchHit::from).collect(),
+    })
+}
+
+/// Format search results as a numbered Markdown list
+pub fn format_results(query: &str, results: &CrateSearchResults) -> String {
+    if results.crates.is_empty() {
+        return format!("No crates found matching \"{}\"", query);
     }
 
     let mut result = format!(
         "Found {} crates matching \"{}\" (showing top {}):\n\n",
-        data.meta.total,
-        params.query,
-        data.crates.len()
+        results.total,
+        query,
+        results.crates.len()
     );
 
-    for (index, crate_info) in data.crates.iter().enumerate() {
+    for (index, crate_info) in results.crates.iter().enumerate() {
         result.push_str(&format!(
             "{}. **{}** v{}\n",
             index + 1,
             crate_info.name,
-            crate_info.max_version
+            crate_info.version
         ));
 
         if let Some(desc) = &crate_info.description {
@@ -114,7 +182,7 @@ pub async fn handle(client: &Client, params: SearchCratesParams) -> Result<Strin
         result.push('\n');
     }
 
-    Ok(result)
+    result
 }
 
 /// Helper function to suggest similar crate names
@@ -128,6 +196,8 @@ pub async fn suggest_similar_crates(
     let params = SearchCratesParams {
         query: crate_name.to_string(),
         limit,
+        output_format: OutputFormat::default(),
+        output: OutputLimit::default(),
     };
 
     // Build the search URL
@@ -142,7 +212,7 @@ pub async fn suggest_similar_crates(
         .get(&search_url)
         .send()
         .await
-        .context("Failed to send request to crates.io")?;
+        .map_err(|e| DocsError::network("crates.io", &e))?;
 
     if !response.status().is_success() {
         return Ok(Vec::new()); // Return empty vector on error
@@ -158,6 +228,104 @@ pub async fn suggest_similar_crates(
     Ok(data.crates.into_iter().map(|c| c.name).collect())
 }
 
+/// Crates.io versions response structure
+#[derive(Debug, Deserialize)]
+struct CratesIoVersionsResponse {
+    versions: Vec<VersionInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionInfo {
+    num: String,
+    yanked: bool,
+}
+
+/// The published versions of a crate that aren't yanked, newest first
+pub async fn crate_versions(client: &Client, crate_name: &str) -> Result<Vec<String>> {
+    let url = format!(
+        "https://crates.io/api/v1/crates/{}/versions",
+        urlencoding::encode(crate_name)
+    );
+
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| DocsError::network("crates.io", &e))?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(DocsError::CrateNotFound {
+            crate_name: crate_name.to_string(),
+        }
+        .into());
+    }
+    if !response.status().is_success() {
+        return Err(DocsError::upstream_status("crates.io", response.status()).into());
+    }
+
+    let data: CratesIoVersionsResponse = response
+        .json()
+        .await
+        .context("Failed to parse crates.io response")?;
+
+    Ok(data
+        .versions
+        .into_iter()
+        .filter(|version| !version.yanked)
+        .map(|version| version.num)
+        .collect())
+}
+
+/// Crates.io dependencies response structure
+#[derive(Debug, Deserialize)]
+struct CratesIoDependenciesResponse {
+    dependencies: Vec<DependencyInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DependencyInfo {
+    crate_id: String,
+    req: String,
+}
+
+/// Look up the version requirement a crate release declares for one of its
+/// dependencies, e.g. `^1.0`. Names are compared ignoring `-` vs `_`, since
+/// rustdoc refers to crates by their library name.
+pub async fn dependency_requirement(
+    client: &Client,
+    crate_name: &str,
+    version: &str,
+    dependency: &str,
+) -> Result<Option<String>> {
+    let url = format!(
+        "https://crates.io/api/v1/crates/{}/{}/dependencies",
+        urlencoding::encode(crate_name),
+        urlencoding::encode(version)
+    );
+
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| DocsError::network("crates.io", &e))?;
+
+    if !response.status().is_success() {
+        return Ok(None);
+    }
+
+    let data: CratesIoDependenciesResponse = response
+        .json()
+        .await
+        .context("Failed to parse crates.io response")?;
+
+    let normalize = |name: &str| name.replace('-', "_");
+    Ok(data
+        .dependencies
+        .into_iter()
+        .find(|dep| normalize(&dep.crate_id) == normalize(dependency))
+        .map(|dep| dep.req))
+}
+
 /// Format a number with thousand separators
 fn format_number(n: u64) -> String {
     let s = n.to_string();
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::crate_cache::CrateCache;
+use crate::rustdoc_parser;
+use crate::tools::output::OutputLimit;
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SearchItemsParams {
+    /// Name of the Rust crate
+    #[serde(rename = "crateName")]
+    pub crate_name: String,
+
+    /// Words to search for in item names, paths and documentation
+    pub query: String,
+
+    /// Only return items of this kind (e.g., "struct", "enum", "trait", "fn", "type", "const", "macro", "mod")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+
+    /// Only return items under this module path (e.g., "blocking" or "reqwest::blocking")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+
+    /// Maximum number of results to return (default: 20)
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+
+    /// Specific version or semver range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// Target platform
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+
+    /// Response size limit and continuation cursor
+    #[serde(flatten)]
+    pub output: OutputLimit,
+}
+
+fn default_limit() -> usize {
+    20
+}
+
+pub async fn handle(crates: &CrateCache, params: SearchItemsParams) -> Result<String> {
+    tracing::info!(
+        "Searching items for '{}' in crate {} (version: {:?})",
+        params.query,
+        params.crate_name,
+        params.version
+    );
+
+    // Fetch and parse the rustdoc JSON (cached across calls)
+    let krate = crates
+        .get(
+            &params.crate_name,
+            params.version.as_deref(),
+            params.target.as_deref(),
+        )
+        .await?;
+
+    // Building the index walks every item's docs, keep it off the async workers
+    let content = tokio::task::spawn_blocking(move || {
+        rustdoc_parser::search_items(
+            &krate,
+            &params.query,
+            params.kind.as_deref(),
+            params.module.as_deref(),
+            params.limit,
+        )
+    })
+    .await??;
+
+    params.output.apply(content)
+}